@@ -240,10 +240,20 @@ class ASLANParser:
         return self.current_value
 
     def exit_delimiter_into_data(self, char: str) -> None:
-        self.current_value += self.delimiter_buffer + char
+        self.current_value += self.delimiter_buffer
         self.delimiter_buffer = ''
         self.current_delimiter = None
         self.state = ASLANParserState.DATA
+        if char == '[' and not self.parsing_locked:
+            # Spec: Every `[` starts buffering a possible delimiter (Implementation specifics), so the char that broke this one may start the next, e.g. `[x[asland_y]`
+            self.store_current_value()
+            self.state = ASLANParserState.MAYBE_DELIMITER
+            self.delimiter_buffer += char
+            return
+        self.current_value += char
+        if not self.parsing_locked:
+            # Store now, as the next char may already start another delimiter
+            self.store_current_value()
 
     def handle_next_char(self, char: str) -> None:
         if self.state == ASLANParserState.GO_DELIMITER:
@@ -836,6 +846,8 @@ class ASLANParser:
             self.delimiter_buffer = ''
             self.current_value = ''
             self.stack[len(self.stack)-1]['voidFields'][self.get_current_key()] = True
+            # Spec: A void field is null. Store the null now, as the next char may already start another delimiter
+            self.store_current_value()
             return
         # Spec: Void delimiters have no <CONTENT> or args
         # INVALID VOID DELIMITER
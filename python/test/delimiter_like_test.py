@@ -13,3 +13,23 @@ def test_parses_simple_string_with_parts_and_delimiter_like_items():
             'This is the third part.',
         ]
     }
+
+def test_parses_delimiter_directly_after_broken_delimiter():
+    parser = ASLANParser()
+    result = parser.parse('[asland_a][x[asland_b]see [asl[asland_c]arr[[asland_d]done')
+    assert result == {
+        '_default': None,
+        'a': '[x',
+        'b': 'see [asl',
+        'c': 'arr[',
+        'd': 'done',
+    }
+
+def test_keeps_broken_delimiter_followed_by_delimiter():
+    parser = ASLANParser()
+    result = parser.parse('[asland_a]1[asl][asland_b]2')
+    assert result == {
+        '_default': None,
+        'a': '1[asl]',
+        'b': '2',
+    }
@@ -56,3 +56,16 @@ class TestASLANParserVoid:
                 'x': 'hi'
             }
         }
+
+    def test_parses_void_directly_followed_by_delimiter(self):
+        result = self.parser.parse(
+            '[asland_a][aslanv][asland_b][aslano][asland_c][aslanv][aslano][asland_d]x'
+        )
+        assert result == {
+            '_default': None,
+            'a': None,
+            'b': {
+                'c': None
+            },
+            'd': 'x'
+        }
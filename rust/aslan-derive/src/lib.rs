@@ -0,0 +1,331 @@
+//! Derive macro for the `aslan` crate's `AslanSchema` trait.
+//!
+//! Supported attributes:
+//! - container: `#[aslan(allow_unknown_fields)]`
+//! - field: `#[aslan(rename = "name")]`, `#[aslan(skip)]`, `#[aslan(parts)]`,
+//!   `#[aslan(duplicate = "append" | "first" | "last")]`, `#[aslan(instructions("bold", "italic"))]`,
+//!   `#[aslan(escape)]`, `#[aslan(description = "...")]`
+//! - variant: `#[aslan(rename = "name")]`, `#[aslan(skip)]`, `#[aslan(description = "...")]`
+//!
+//! Doc comments are used as descriptions unless `description` is given. Field attributes that
+//! don't apply to variants are rejected:
+//!
+//! ```compile_fail
+//! #[derive(aslan::AslanSchema)]
+//! enum Block {
+//!     #[aslan(parts)]
+//!     Text(String),
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{
+    parenthesized, parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Expr, Fields, GenericParam, Lit,
+    LitStr, Meta, Token,
+};
+
+#[proc_macro_derive(AslanSchema, attributes(aslan))]
+pub fn derive_aslan_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    allow_unknown_fields: bool,
+    description: Option<String>,
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    skip: bool,
+    parts: bool,
+    escape: bool,
+    duplicate: Option<TokenStream2>,
+    instructions: Option<Vec<String>>,
+    description: Option<String>,
+}
+
+fn expand(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let container = parse_container_attrs(&input.attrs)?;
+
+    // Every type parameter must describe its own schema
+    let type_params: Vec<_> = input
+        .generics
+        .params
+        .iter()
+        .filter_map(|p| match p {
+            GenericParam::Type(t) => Some(t.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    let where_clause = input.generics.make_where_clause();
+    for ident in type_params {
+        where_clause.predicates.push(parse_quote!(#ident: ::aslan::AslanSchema));
+    }
+
+    let allow_unknown_fields = container.allow_unknown_fields;
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => {
+                let fields = expand_fields(&data.fields)?;
+                quote! {
+                    let mut schema = ::aslan::ASLANSchema::object();
+                    schema.additional_fields = #allow_unknown_fields;
+                    #fields
+                }
+            }
+            Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+                let ty = &unnamed.unnamed[0].ty;
+                quote! {
+                    let mut schema = <#ty as ::aslan::AslanSchema>::aslan_schema();
+                }
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "AslanSchema can only be derived for structs with named fields or a single unnamed field",
+                ))
+            }
+        },
+        Data::Enum(data) => {
+            let all_unit = data.variants.iter().all(|v| matches!(v.fields, Fields::Unit));
+            if all_unit {
+                let mut names = Vec::new();
+                for variant in &data.variants {
+                    let attrs = parse_field_attrs(&variant.attrs, true)?;
+                    if attrs.skip {
+                        continue;
+                    }
+                    names.push(attrs.rename.unwrap_or_else(|| variant.ident.to_string()));
+                }
+                quote! {
+                    let mut schema = ::aslan::ASLANSchema::string();
+                    schema.allowed_values = Some(vec![#(#names.to_string()),*]);
+                }
+            } else {
+                // Externally tagged: an object with one optional field per variant
+                let mut variants = Vec::new();
+                for variant in &data.variants {
+                    let attrs = parse_field_attrs(&variant.attrs, true)?;
+                    if attrs.skip {
+                        continue;
+                    }
+                    let name = attrs.rename.clone().unwrap_or_else(|| variant.ident.to_string());
+                    let variant_schema = match &variant.fields {
+                        Fields::Unit => quote! { ::aslan::ASLANSchema::string().nullable() },
+                        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+                            let ty = &unnamed.unnamed[0].ty;
+                            quote! { <#ty as ::aslan::AslanSchema>::aslan_schema() }
+                        }
+                        Fields::Named(_) => {
+                            let fields = expand_fields(&variant.fields)?;
+                            quote! {{
+                                let mut schema = ::aslan::ASLANSchema::object();
+                                schema.additional_fields = false;
+                                #fields
+                                schema
+                            }}
+                        }
+                        Fields::Unnamed(_) => {
+                            return Err(syn::Error::new_spanned(
+                                variant,
+                                "AslanSchema does not support tuple variants with more than one field",
+                            ))
+                        }
+                    };
+                    let description = set_description(&attrs.description);
+                    variants.push(quote! {{
+                        let mut field_schema = #variant_schema;
+                        #description
+                        schema.fields.push(::aslan::ASLANSchemaField::new(#name, field_schema, false));
+                    }});
+                }
+                quote! {
+                    let mut schema = ::aslan::ASLANSchema::object();
+                    schema.additional_fields = #allow_unknown_fields;
+                    #(#variants)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(&input.ident, "AslanSchema cannot be derived for unions"));
+        }
+    };
+
+    let description = match &container.description {
+        Some(description) => quote! { schema.description = Some(#description.to_string()); },
+        None => quote! {},
+    };
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::aslan::AslanSchema for #ident #ty_generics #where_clause {
+            #[allow(unused_mut)]
+            fn aslan_schema() -> ::aslan::ASLANSchema {
+                #body
+                #description
+                schema
+            }
+        }
+    })
+}
+
+fn expand_fields(fields: &Fields) -> syn::Result<TokenStream2> {
+    let mut out = Vec::new();
+    for field in fields {
+        let attrs = parse_field_attrs(&field.attrs, false)?;
+        if attrs.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named field");
+        let name = attrs
+            .rename
+            .clone()
+            .unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_string());
+        let ty = &field.ty;
+        let base = if attrs.parts {
+            quote! {{
+                let mut schema = ::aslan::ASLANSchema::parts();
+                schema.nullable = <#ty as ::aslan::AslanSchema>::aslan_optional();
+                schema
+            }}
+        } else {
+            quote! { <#ty as ::aslan::AslanSchema>::aslan_schema() }
+        };
+        let instructions = match &attrs.instructions {
+            Some(instructions) => quote! {
+                field_schema.allowed_instructions = Some(vec![#(#instructions.to_string()),*]);
+            },
+            None => quote! {},
+        };
+        let escape = if attrs.escape {
+            quote! { field_schema.escaped = true; }
+        } else {
+            quote! {}
+        };
+        let description = set_description(&attrs.description);
+        let duplicate = match &attrs.duplicate {
+            Some(behavior) => quote! { Some(#behavior) },
+            None => quote! { None },
+        };
+        out.push(quote! {{
+            let mut field_schema = #base;
+            #instructions
+            #escape
+            #description
+            let mut field = ::aslan::ASLANSchemaField::new(
+                #name,
+                field_schema,
+                !<#ty as ::aslan::AslanSchema>::aslan_optional(),
+            );
+            field.duplicate_behavior = #duplicate;
+            schema.fields.push(field);
+        }});
+    }
+    Ok(quote! { #(#out)* })
+}
+
+fn set_description(description: &Option<String>) -> TokenStream2 {
+    match description {
+        Some(description) => quote! { field_schema.description = Some(#description.to_string()); },
+        None => quote! {},
+    }
+}
+
+fn parse_container_attrs(attrs: &[Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut parsed = ContainerAttrs {
+        description: doc_comment(attrs),
+        ..Default::default()
+    };
+    for attr in attrs.iter().filter(|a| a.path().is_ident("aslan")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("allow_unknown_fields") {
+                parsed.allow_unknown_fields = true;
+                Ok(())
+            } else if meta.path.is_ident("description") {
+                let value: LitStr = meta.value()?.parse()?;
+                parsed.description = Some(value.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported aslan container attribute"))
+            }
+        })?;
+    }
+    Ok(parsed)
+}
+
+/// Parse the attributes of a field, or of an enum variant, which only takes `rename`, `skip`
+/// and `description`
+fn parse_field_attrs(attrs: &[Attribute], is_variant: bool) -> syn::Result<FieldAttrs> {
+    let mut parsed = FieldAttrs {
+        description: doc_comment(attrs),
+        ..Default::default()
+    };
+    for attr in attrs.iter().filter(|a| a.path().is_ident("aslan")) {
+        attr.parse_nested_meta(|meta| {
+            for name in ["parts", "escape", "duplicate", "instructions"] {
+                if is_variant && meta.path.is_ident(name) {
+                    return Err(meta.error(format!("`{}` only applies to fields, not to enum variants", name)));
+                }
+            }
+            if meta.path.is_ident("rename") {
+                let value: LitStr = meta.value()?.parse()?;
+                parsed.rename = Some(value.value());
+            } else if meta.path.is_ident("skip") {
+                parsed.skip = true;
+            } else if meta.path.is_ident("parts") {
+                parsed.parts = true;
+            } else if meta.path.is_ident("escape") {
+                parsed.escape = true;
+            } else if meta.path.is_ident("description") {
+                let value: LitStr = meta.value()?.parse()?;
+                parsed.description = Some(value.value());
+            } else if meta.path.is_ident("duplicate") {
+                let value: LitStr = meta.value()?.parse()?;
+                parsed.duplicate = Some(match value.value().as_str() {
+                    "append" | "a" => quote! { ::aslan::ASLANDuplicateKeyBehavior::Append },
+                    "first" | "f" => quote! { ::aslan::ASLANDuplicateKeyBehavior::KeepFirst },
+                    "last" | "l" => quote! { ::aslan::ASLANDuplicateKeyBehavior::KeepLast },
+                    _ => return Err(meta.error("duplicate must be one of \"append\", \"first\" or \"last\"")),
+                });
+            } else if meta.path.is_ident("instructions") {
+                let content;
+                parenthesized!(content in meta.input);
+                let instructions = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+                parsed.instructions = Some(instructions.iter().map(|i| i.value()).collect());
+            } else {
+                return Err(meta.error("unsupported aslan attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
+
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(lit) => match &lit.lit {
+                    Lit::Str(s) => Some(s.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(" "))
+    }
+}
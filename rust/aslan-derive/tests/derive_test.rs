@@ -0,0 +1,139 @@
+use aslan::{ASLANDuplicateKeyBehavior, ASLANParser, ASLANParserSettings, ASLANSchemaType, AslanSchema};
+use serde_json::json;
+
+/// A news article
+#[derive(AslanSchema)]
+#[allow(dead_code)]
+struct Article {
+    /// The headline
+    title: String,
+    #[aslan(parts, instructions("bold", "italic"))]
+    body: Vec<String>,
+    #[aslan(rename = "tag_list")]
+    tags: Vec<String>,
+    #[aslan(duplicate = "last")]
+    status: Status,
+    author: Option<Author>,
+    word_count: u32,
+    #[aslan(skip)]
+    internal_id: u64,
+}
+
+#[derive(AslanSchema)]
+#[allow(dead_code)]
+struct Author {
+    name: String,
+    verified: bool,
+}
+
+#[derive(AslanSchema)]
+#[allow(dead_code)]
+enum Status {
+    Draft,
+    #[aslan(rename = "live")]
+    Published,
+}
+
+#[derive(AslanSchema)]
+#[allow(dead_code)]
+enum Action {
+    Reply { text: String },
+    Wait(f64),
+    Stop,
+}
+
+#[derive(AslanSchema)]
+#[allow(dead_code)]
+struct Snippet {
+    #[aslan(escape)]
+    code: String,
+    language: String,
+}
+
+#[derive(AslanSchema)]
+#[aslan(allow_unknown_fields)]
+#[allow(dead_code)]
+struct Page<T> {
+    items: Vec<T>,
+}
+
+#[test]
+fn derives_struct_schema() {
+    let schema = Article::aslan_schema();
+    assert_eq!(schema.schema_type, ASLANSchemaType::Object);
+    assert_eq!(schema.description.as_deref(), Some("A news article"));
+    assert!(!schema.additional_fields);
+
+    let names: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["title", "body", "tag_list", "status", "author", "word_count"]);
+
+    let title = schema.get_field("title").unwrap();
+    assert!(title.required);
+    assert_eq!(title.schema.description.as_deref(), Some("The headline"));
+
+    let body = &schema.get_field("body").unwrap().schema;
+    assert_eq!(body.schema_type, ASLANSchemaType::Parts);
+    assert_eq!(body.allowed_instructions, Some(vec!["bold".to_string(), "italic".to_string()]));
+
+    let status = schema.get_field("status").unwrap();
+    assert_eq!(status.duplicate_behavior, Some(ASLANDuplicateKeyBehavior::KeepLast));
+    assert_eq!(status.schema.allowed_values, Some(vec!["Draft".to_string(), "live".to_string()]));
+
+    let author = schema.get_field("author").unwrap();
+    assert!(!author.required);
+    assert!(author.schema.nullable);
+    assert_eq!(author.schema.get_field("verified").unwrap().schema.schema_type, ASLANSchemaType::Boolean);
+
+    assert_eq!(schema.get_field("word_count").unwrap().schema.schema_type, ASLANSchemaType::Integer);
+}
+
+#[test]
+fn marks_escaped_fields() {
+    let schema = Snippet::aslan_schema();
+    assert!(schema.get_field("code").unwrap().schema.escaped);
+    assert!(!schema.get_field("language").unwrap().schema.escaped);
+}
+
+#[test]
+fn derives_enum_with_data_as_tagged_object() {
+    let schema = Action::aslan_schema();
+    assert_eq!(schema.schema_type, ASLANSchemaType::Object);
+    let reply = schema.get_field("Reply").unwrap();
+    assert!(!reply.required);
+    assert_eq!(reply.schema.get_field("text").unwrap().schema.schema_type, ASLANSchemaType::String);
+    assert_eq!(schema.get_field("Wait").unwrap().schema.schema_type, ASLANSchemaType::Number);
+    assert!(schema.get_field("Stop").unwrap().schema.nullable);
+}
+
+#[test]
+fn derives_generic_struct() {
+    let schema = Page::<Author>::aslan_schema();
+    assert!(schema.additional_fields);
+    let items = schema.get_field("items").unwrap().schema.items.as_ref().unwrap();
+    assert_eq!(items.get_field("name").unwrap().schema.schema_type, ASLANSchemaType::String);
+}
+
+#[test]
+fn example_parses_back_to_the_derived_shape() {
+    let example = Article::aslan_example("aslan");
+    assert!(example.starts_with("[asland_title]example title\n[asland_body][aslanp][aslani_bold]example body part 1"));
+    assert!(example.contains("[asland_status:l]Draft\n"));
+
+    let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+        schema: Some(Article::aslan_schema()),
+        coerce_leaves: true,
+        ..Default::default()
+    });
+    let result = parser.parse(&example);
+    assert_eq!(parser.get_violations(), vec![]);
+    assert_eq!(result["author"], json!({ "name": "example name\n", "verified": true }));
+    assert_eq!(result["word_count"], json!(42));
+}
+
+#[test]
+fn describes_format() {
+    let description = Article::aslan_format_description();
+    assert!(description.starts_with("- `title`: text (required) - The headline\n"));
+    assert!(description.contains("- `status`: text, one of `Draft`, `live` (required, if repeated, the last value is kept)\n"));
+    assert!(description.contains("- `author`: object, may be void (optional)\n  - `name`: text (required)\n"));
+}
@@ -1,11 +1,19 @@
+use crate::diagnostics::{ASLANDiagnostic, ASLANDiagnosticCode};
+use crate::error::{ASLANError, ASLANSettingsError};
+use crate::extension::{ASLANDelimiterExtension, ASLANExtensionContent, ASLANExtensionContext, ASLANExtensionDelimiter, ASLANExtensionEvent};
+use crate::lexer::{ASLANBlockEdge, ASLANToken, ASLANTokenKind};
 use crate::recent_items::RecentItems;
+use crate::settings::ASLANParserSettingsBuilder;
+use crate::schema::{self, ASLANSchema, ASLANSchemaType, ASLANViolation, ASLANViolationKind};
 use crate::utils::generate_random_idempotency_key;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Delimiter types in ASLAN
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ASLANDelimiterType {
     Data,
     Object,
@@ -20,7 +28,7 @@ pub enum ASLANDelimiterType {
 }
 
 /// Duplicate key behavior
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ASLANDuplicateKeyBehavior {
     Append,
     KeepFirst,
@@ -45,6 +53,7 @@ enum ASLANParserState {
     MaybeDelimiter,
     Delimiter,
     ReservedDelimiter,
+    ExtensionDelimiter,
     Object,
     Array,
     Comment,
@@ -75,6 +84,32 @@ struct ASLANDelimiterData {
     suffix: Option<ASLANDelimiterType>,
     content: Option<String>,
     args: Vec<String>,
+    /// Suffix of a registered extension
+    extension: Option<char>,
+    /// How the current arg is read when `quoted_args` is enabled
+    arg_quoting: ASLANArgQuoting,
+}
+
+/// Position inside a delimiter arg when `quoted_args` is enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ASLANArgQuoting {
+    #[default]
+    Plain,
+    PlainEscape,
+    Quoted,
+    QuotedEscape,
+    AfterQuote,
+}
+
+/// What a char does to the delimiter arg being read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ASLANArgChar {
+    /// The char belongs to the arg and has been consumed
+    Consumed,
+    /// A `:` or `]` ending the arg
+    Separator,
+    /// The char cannot appear here and breaks the delimiter
+    Invalid,
 }
 
 /// Key type - either string or numeric index
@@ -101,6 +136,21 @@ pub struct ASLANRegisteredInstruction {
     pub index: usize,
     pub args: Vec<String>,
     pub part_index: usize,
+    #[serde(default)]
+    pub named_args: HashMap<String, String>,
+    /// Opened with a `start` marker, see `ranged_instructions`
+    #[serde(default)]
+    pub ranged: bool,
+    /// Index of the matching `end` marker
+    #[serde(default)]
+    pub end: Option<usize>,
+}
+
+impl ASLANRegisteredInstruction {
+    /// The range of a ranged instruction. Ranges without an `end` marker end with their part
+    fn range(&self, part_length: usize) -> Option<(usize, usize)> {
+        self.ranged.then(|| (self.index, self.end.unwrap_or(part_length)))
+    }
 }
 
 /// Instruction event data
@@ -113,13 +163,22 @@ pub struct ASLANInstruction {
     pub structure: Value,
     pub instruction: String,
     pub args: Vec<String>,
+    /// `name=value` args, set when `named_args` is enabled
+    #[serde(default)]
+    pub named_args: HashMap<String, String>,
     pub index: usize,
+    /// Start and end index of a ranged instruction, see `ranged_instructions`
+    #[serde(default)]
+    pub range: Option<(usize, usize)>,
     pub multi_aslan_index: usize,
     pub tag: String,
+    /// Input ranges of the field, set when `track_spans` is enabled
+    #[serde(default)]
+    pub spans: Option<ASLANValueSpans>,
 }
 
 /// Content part with instructions
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ASLANContentPart {
     pub value: String,
     pub part_index: usize,
@@ -127,11 +186,17 @@ pub struct ASLANContentPart {
 }
 
 /// Instruction info for end_data events
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ASLANInstructionInfo {
     pub name: String,
     pub args: Vec<String>,
+    /// `name=value` args, set when `named_args` is enabled
+    #[serde(default)]
+    pub named_args: HashMap<String, String>,
     pub index: usize,
+    /// Start and end index of a ranged instruction, see `ranged_instructions`
+    #[serde(default)]
+    pub range: Option<(usize, usize)>,
 }
 
 /// End data instruction event
@@ -143,6 +208,44 @@ pub struct ASLANEndDataInstruction {
     pub structure: Value,
     pub multi_aslan_index: usize,
     pub tag: String,
+    /// Input ranges of the field, set when `track_spans` is enabled
+    #[serde(default)]
+    pub spans: Option<ASLANValueSpans>,
+    /// `name=value` args of the field's data delimiters, set when `named_args` is enabled
+    #[serde(default)]
+    pub annotations: HashMap<String, String>,
+    /// Unit the `index` and `range` of the instructions in `content` are counted in
+    #[serde(default)]
+    pub index_unit: ASLANIndexUnit,
+}
+
+/// A range of input, as byte offsets into everything passed to the parser so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ASLANSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Input ranges that produced a value in the result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ASLANValueSpans {
+    /// Keys from the root of the result down to the value, including `_default`
+    pub path: Vec<String>,
+    pub multi_aslan_index: usize,
+    /// Data, part, void, instruction, escape, object and array delimiters applying to the value
+    pub delimiters: Vec<ASLANSpan>,
+    /// Text stored in the value. Appended duplicates and parts each add their own range
+    pub content: Vec<ASLANSpan>,
+}
+
+/// `name=value` args of the data delimiters of a field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ASLANFieldAnnotations {
+    /// Keys from the root of the result down to the field
+    pub path: Vec<String>,
+    pub multi_aslan_index: usize,
+    /// Later data delimiters of the same field overwrite earlier values
+    pub named_args: HashMap<String, String>,
 }
 
 /// Parser stack frame
@@ -157,10 +260,39 @@ struct ASLANParserStateStackFrame {
     already_seen_duplicate_keys: HashMap<String, bool>,
     implicit_arrays: HashMap<String, bool>,
     registered_instructions: Vec<ASLANRegisteredInstruction>,
+    /// Indentation of the line the block opened on
+    opening_indent: usize,
+    /// Indentation of the first field of the block that starts its own line
+    child_indent: Option<usize>,
 }
 
-/// Parser settings
-#[derive(Debug, Clone)]
+/// Whether `text` may be the start of a fence line like ` ```aslan\n `, after optional whitespace
+fn is_opening_fence_prefix(text: &str) -> bool {
+    let rest = text.trim_start();
+    let after_ticks = rest.trim_start_matches('`');
+    let ticks = rest.len() - after_ticks.len();
+    if after_ticks.is_empty() {
+        return true;
+    }
+    if ticks < 3 {
+        return false;
+    }
+    let after_info = after_ticks.trim_start_matches(|c: char| c.is_ascii_alphanumeric() || "_+-.".contains(c));
+    let line_end = after_info.strip_suffix('\n').unwrap_or(after_info);
+    line_end.chars().all(|c| c == ' ' || c == '\t' || c == '\r')
+}
+
+/// Whether `text` may be a line break followed by a closing fence and only whitespace
+fn is_closing_fence_prefix(text: &str) -> bool {
+    let rest = text.strip_prefix('\n').unwrap_or(text).trim_start_matches([' ', '\t']);
+    let after_ticks = rest.trim_start_matches('`');
+    let ticks = rest.len() - after_ticks.len();
+    after_ticks.is_empty() || (ticks >= 3 && after_ticks.chars().all(char::is_whitespace))
+}
+
+/// Parser settings. Use `ASLANParserSettings::builder()` to validate them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "ASLANParserSettingsBuilder")]
 pub struct ASLANParserSettings {
     pub prefix: String,
     pub default_field_name: String,
@@ -171,15 +303,129 @@ pub struct ASLANParserSettings {
     pub collapse_object_start_whitespace: bool,
     pub append_separator: String,
     pub max_object_depth: Option<usize>,
+    /// Expected structure used to decide whether object and array delimiters open or close,
+    /// and to report violations while parsing
+    pub schema: Option<ASLANSchema>,
+    /// Trim and convert integer, number and boolean leaves described by the schema on close
+    pub coerce_leaves: bool,
+    /// Record the input ranges of every value, see `ASLANParser::spans`
+    pub track_spans: bool,
+    /// Make `try_parse`, `try_parse_next` and `try_close` fail on any input that is otherwise
+    /// repaired silently, and on schema violations
+    pub strict: bool,
+    /// Also recognize delimiters with these prefixes, merging them into the same result.
+    /// Use `ASLANPrefixRouter` to keep each prefix in its own result instead
+    pub additional_prefixes: Vec<String>,
+    /// Ignore `prefix` and lock onto the prefix of the first well-formed data delimiter instead,
+    /// see `ASLANParser::detected_prefix`
+    pub detect_prefix: bool,
+    /// Only detect these prefixes. Any alphanumeric prefix is detected when empty
+    pub allowed_prefixes: Vec<String>,
+    /// Near-miss delimiter forms to accept
+    pub tolerance: ASLANTolerance,
+    /// Discard a Markdown code fence line like ` ```aslan ` before the first delimiter, and the
+    /// matching closing fence at the end of the input
+    pub strip_code_fences: bool,
+    /// Close objects and arrays whose fields are indented when a later field is indented no
+    /// further than the line the block opened on
+    pub repair_indentation: bool,
+    /// Unit of the `index` of instructions
+    pub instruction_index_unit: ASLANIndexUnit,
+    /// Read args of instruction, data and custom delimiters in `"` verbatim, so they may contain
+    /// `:` and `]`. Outside and inside quotes `\` takes the next char literally
+    pub quoted_args: bool,
+    /// Also read args of the form `name=value` into `named_args` of instructions and into the
+    /// annotations of data fields, see `ASLANParser::annotations`
+    pub named_args: bool,
+    /// Read instructions whose first arg is `start` or `end` as the bounds of a range, as in
+    /// `[aslani_bold:start]bold[aslani_bold:end]`. Ranges without an end marker end with their part
+    pub ranged_instructions: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EmittableEvents {
     pub content: bool,
     pub end: bool,
     pub end_data: bool,
 }
 
+/// Rules for accepting delimiters that models commonly get slightly wrong. Each accepted
+/// delimiter is reported as a `RepairedDelimiter` diagnostic
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ASLANTolerance {
+    /// Accept the prefix and suffix in any case, e.g. `[ASLANd_title]`
+    pub ignore_case: bool,
+    /// Accept a space between the prefix and the suffix, e.g. `[aslan d_title]`
+    pub space_after_prefix: bool,
+    /// Accept an underscore between the prefix and the suffix, e.g. `[aslan_d_title]`
+    pub underscore_after_prefix: bool,
+    /// Accept data and instruction names without their underscore, e.g. `[aslandtitle]`
+    pub missing_underscore: bool,
+    /// Accept markdown-escaped brackets, e.g. `\[asland_title\]`
+    pub escaped_brackets: bool,
+}
+
+impl ASLANTolerance {
+    /// Enable every rule
+    pub fn all() -> Self {
+        Self {
+            ignore_case: true,
+            space_after_prefix: true,
+            underscore_after_prefix: true,
+            missing_underscore: true,
+            escaped_brackets: true,
+        }
+    }
+}
+
+/// How text positions are counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ASLANIndexUnit {
+    /// UTF-8 bytes, for slicing Rust strings
+    #[default]
+    Bytes,
+    /// Unicode scalar values, as counted by `str::chars`
+    Chars,
+    /// UTF-16 code units, as used by JavaScript strings
+    Utf16,
+    /// Extended grapheme clusters, as perceived by readers
+    Graphemes,
+}
+
+impl ASLANIndexUnit {
+    /// The length of `text` in this unit
+    pub fn length(&self, text: &str) -> usize {
+        match self {
+            ASLANIndexUnit::Bytes => text.len(),
+            ASLANIndexUnit::Chars => text.chars().count(),
+            ASLANIndexUnit::Utf16 => text.encode_utf16().count(),
+            ASLANIndexUnit::Graphemes => text.graphemes(true).count(),
+        }
+    }
+
+    /// The byte offset of `index` in this unit into `text`. Indices inside a char round up,
+    /// and indices past the end give the length of `text`
+    pub fn byte_offset(&self, text: &str, index: usize) -> usize {
+        match self {
+            ASLANIndexUnit::Bytes => (index.min(text.len())..=text.len()).find(|&i| text.is_char_boundary(i)).unwrap_or(text.len()),
+            ASLANIndexUnit::Chars => text.char_indices().nth(index).map_or(text.len(), |(i, _)| i),
+            ASLANIndexUnit::Utf16 => {
+                let mut units = 0;
+                for (i, c) in text.char_indices() {
+                    if units >= index {
+                        return i;
+                    }
+                    units += c.len_utf16();
+                }
+                text.len()
+            }
+            ASLANIndexUnit::Graphemes => text.grapheme_indices(true).nth(index).map_or(text.len(), |(i, _)| i),
+        }
+    }
+}
+
 impl Default for EmittableEvents {
     fn default() -> Self {
         Self {
@@ -202,6 +448,20 @@ impl Default for ASLANParserSettings {
             collapse_object_start_whitespace: true,
             append_separator: String::new(),
             max_object_depth: None,
+            schema: None,
+            coerce_leaves: false,
+            track_spans: false,
+            strict: false,
+            additional_prefixes: Vec::new(),
+            detect_prefix: false,
+            allowed_prefixes: Vec::new(),
+            tolerance: ASLANTolerance::default(),
+            strip_code_fences: false,
+            repair_indentation: false,
+            instruction_index_unit: ASLANIndexUnit::default(),
+            quoted_args: false,
+            named_args: false,
+            ranged_instructions: false,
         }
     }
 }
@@ -210,22 +470,17 @@ impl Default for ASLANParserSettings {
 pub type ContentEventHandler = Box<dyn FnMut(&ASLANInstruction)>;
 pub type EndEventHandler = Box<dyn FnMut(&ASLANInstruction)>;
 pub type EndDataEventHandler = Box<dyn FnMut(&ASLANEndDataInstruction)>;
+pub type ViolationEventHandler = Box<dyn FnMut(&ASLANViolation)>;
+pub type ExtensionEventHandler = Box<dyn FnMut(&ASLANExtensionEvent)>;
 
 /// Event listeners
+#[derive(Default)]
 struct ASLANEventListeners {
     content: Vec<(String, ContentEventHandler)>,
     end: Vec<(String, EndEventHandler)>,
     end_data: Vec<(String, EndDataEventHandler)>,
-}
-
-impl Default for ASLANEventListeners {
-    fn default() -> Self {
-        Self {
-            content: Vec::new(),
-            end: Vec::new(),
-            end_data: Vec::new(),
-        }
-    }
+    violation: Vec<(String, ViolationEventHandler)>,
+    extension: Vec<(String, ExtensionEventHandler)>,
 }
 
 /// The ASLAN Parser
@@ -235,15 +490,48 @@ pub struct ASLANParser {
     current_delimiter: Option<ASLANDelimiterData>,
     current_value: String,
     delimiter_buffer: String,
-    delimiter_open_substring: String,
+    delimiter_open_substrings: Vec<String>,
+    /// Set until a detected prefix forms its first well-formed delimiter
+    detecting_prefix: bool,
+    detected_prefix: Option<String>,
+    /// Tolerance rules the delimiter being built needed
+    delimiter_repairs: Vec<&'static str>,
+    /// Input held back while it may still be a code fence
+    fence_buffer: String,
+    opening_fence_pending: bool,
+    /// Whether a line may still start the opening fence, until the first delimiter
+    awaiting_opening_fence: bool,
+    inside_code_fences: bool,
+    /// Indentation of the current line, tabs counting as one column
+    line_indent: usize,
+    at_line_start: bool,
+    /// Offset of the first char of the current line that is not indentation
+    line_content_start: usize,
     recent_delimiters: RecentItems<ASLANDelimiterType>,
     current_escape_delimiter: Option<String>,
     parsing_locked: bool,
     parser_settings: ASLANParserSettings,
     multi_aslan_results: Vec<Value>,
     did_stop: bool,
+    /// Set by `close` until more input arrives
+    closed: bool,
+    /// The result with coerced leaves, set by `close` until more input arrives. The stored
+    /// result keeps the raw text, as later input appends to it
+    coerced_result: Option<Value>,
     event_listeners: ASLANEventListeners,
     listener_idempotency_keys: HashSet<String>,
+    extensions: Vec<Box<dyn ASLANDelimiterExtension>>,
+    violations: Vec<ASLANViolation>,
+    reported_violations: HashSet<(ASLANViolationKind, Vec<String>)>,
+    input_offset: usize,
+    spans: Vec<ASLANValueSpans>,
+    annotations: Vec<ASLANFieldAnnotations>,
+    diagnostics: Vec<ASLANDiagnostic>,
+    /// Set while lexing, see `lexer::lex`
+    tokens: Option<Vec<ASLANToken>>,
+    lexed_input: String,
+    lexed_input_start: usize,
+    lexed_until: usize,
 }
 
 impl ASLANParser {
@@ -252,9 +540,24 @@ impl ASLANParser {
         Self::with_settings(ASLANParserSettings::default())
     }
 
-    /// Create a new parser with custom settings
+    /// Create a parser that checks `settings` first, like `ASLANParserSettings::builder()` does
+    pub fn try_with_settings(settings: ASLANParserSettings) -> Result<Self, ASLANSettingsError> {
+        settings.validate()?;
+        Ok(Self::with_settings(settings))
+    }
+
+    /// Create a new parser with custom settings. They are used as given, so options that don't
+    /// work together are not reported. Use `try_with_settings` to check them
     pub fn with_settings(settings: ASLANParserSettings) -> Self {
-        let delimiter_open_substring = format!("[{}", settings.prefix);
+        let delimiter_open_substrings = if settings.detect_prefix {
+            Vec::new()
+        } else {
+            std::iter::once(&settings.prefix)
+                .chain(&settings.additional_prefixes)
+                .map(|prefix| format!("[{}", prefix))
+                .collect()
+        };
+        let detecting_prefix = settings.detect_prefix;
         let default_field_name = settings.default_field_name.clone();
         let strict_start = settings.strict_start;
 
@@ -278,6 +581,8 @@ impl ASLANParser {
             already_seen_duplicate_keys: HashMap::new(),
             implicit_arrays: HashMap::new(),
             registered_instructions: Vec::new(),
+            opening_indent: 0,
+            child_indent: None,
         };
 
         let initial_state = if strict_start {
@@ -286,58 +591,149 @@ impl ASLANParser {
             ASLANParserState::Start
         };
 
-        let parser = Self {
+        Self {
             state: initial_state,
             stack: vec![initial_frame],
             current_delimiter: None,
             current_value: String::new(),
             delimiter_buffer: String::new(),
-            delimiter_open_substring,
+            delimiter_open_substrings,
+            detecting_prefix,
+            detected_prefix: None,
+            delimiter_repairs: Vec::new(),
+            fence_buffer: String::new(),
+            opening_fence_pending: settings.strip_code_fences,
+            awaiting_opening_fence: settings.strip_code_fences,
+            inside_code_fences: false,
+            line_indent: 0,
+            at_line_start: true,
+            line_content_start: 0,
             recent_delimiters: RecentItems::default(),
             current_escape_delimiter: None,
             parsing_locked: strict_start,
             parser_settings: settings,
             multi_aslan_results: vec![initial_result],
             did_stop: true,
+            closed: false,
+            coerced_result: None,
             event_listeners: ASLANEventListeners::default(),
+            extensions: Vec::new(),
             listener_idempotency_keys: HashSet::new(),
-        };
-
-        parser
+            violations: Vec::new(),
+            reported_violations: HashSet::new(),
+            input_offset: 0,
+            spans: Vec::new(),
+            annotations: Vec::new(),
+            diagnostics: Vec::new(),
+            tokens: None,
+            lexed_input: String::new(),
+            lexed_input_start: 0,
+            lexed_until: 0,
+        }
     }
 
     /// Parse a complete input string and return the result
     pub fn parse(&mut self, input: &str) -> Value {
         for ch in input.chars() {
-            self.handle_next_char(ch);
+            self.feed_char(ch);
         }
         self.close();
-
-        if self.parser_settings.multi_aslan_output {
-            Value::Array(self.multi_aslan_results.clone())
-        } else {
-            self.stack[0].inner_result.clone()
-        }
+        self.get_output()
     }
 
     /// Parse input incrementally (streaming)
     pub fn parse_next(&mut self, input: &str) {
         for ch in input.chars() {
-            self.handle_next_char(ch);
+            self.feed_char(ch);
+        }
+    }
+
+    /// Parse input incrementally, stopping at the first schema violation.
+    /// Returns `ControlFlow::Break` with the violation so the caller can abort the upstream request.
+    /// Characters after the offending one are not consumed.
+    pub fn parse_next_checked(&mut self, input: &str) -> ControlFlow<ASLANViolation> {
+        for ch in input.chars() {
+            let violation_count = self.violations.len();
+            self.feed_char(ch);
+            if let Some(violation) = self.violations.get(violation_count) {
+                return ControlFlow::Break(violation.clone());
+            }
         }
+        ControlFlow::Continue(())
     }
 
-    /// Close the parser and finalize results
+    /// Parse a complete input string. In strict mode, fails at the first diagnostic of any severity
+    /// or schema violation
+    pub fn try_parse(&mut self, input: &str) -> Result<Value, ASLANError> {
+        self.try_parse_next(input)?;
+        self.try_close()
+    }
+
+    /// Parse input incrementally. In strict mode, stops at the first diagnostic of any severity or
+    /// schema violation.
+    /// Characters after the offending one are not consumed.
+    pub fn try_parse_next(&mut self, input: &str) -> Result<(), ASLANError> {
+        if !self.parser_settings.strict {
+            self.parse_next(input);
+            return Ok(());
+        }
+        self.check_strict()?;
+        for ch in input.chars() {
+            self.feed_char(ch);
+            self.check_strict()?;
+        }
+        Ok(())
+    }
+
+    /// Close the parser and return the result, or every result with `multi_aslan_output` like
+    /// `parse`. In strict mode, fails if any diagnostic was reported, including ones of Info
+    /// severity, or the result violates the schema.
+    pub fn try_close(&mut self) -> Result<Value, ASLANError> {
+        self.close();
+        self.check_strict()?;
+        Ok(self.get_output())
+    }
+
+    fn check_strict(&self) -> Result<(), ASLANError> {
+        if !self.parser_settings.strict {
+            return Ok(());
+        }
+        // Every diagnostic fails, whatever its severity, as each one is a repair lenient mode made
+        if let Some(diagnostic) = self.diagnostics.first() {
+            return Err(diagnostic.clone().into());
+        }
+        if let Some(violation) = self.violations.first() {
+            return Err(violation.clone().into());
+        }
+        Ok(())
+    }
+
+    /// Close the parser and finalize results. Calling it again without new input does nothing
     pub fn close(&mut self) {
+        if self.closed {
+            return;
+        }
+        // Replaying buffered fence input clears `closed`, so set it afterwards
+        self.finish_code_fence();
+        self.closed = true;
+        if self.current_escape_delimiter.is_some() {
+            self.report_diagnostic(ASLANDiagnosticCode::UnterminatedEscape, self.input_offset, "escape is not closed");
+        }
         self.emit_end_events_if_required();
         self.emit_end_data_events_if_required();
-        self.store_current_value();
+        // An unfinished delimiter at the end of the input is dropped
+        self.store_current_value_ending_at(Some(self.input_offset - self.delimiter_buffer.len()));
         self.sync_stack_to_root();
+        self.coerce_result_leaves();
+        self.validate_result_against_schema();
     }
 
-    /// Get the current result
+    /// Get the current result, with leaves coerced once closed if `coerce_leaves` is enabled
     pub fn get_result(&self) -> Value {
-        self.stack[0].inner_result.clone()
+        match &self.coerced_result {
+            Some(result) => result.clone(),
+            None => self.stack[0].inner_result.clone(),
+        }
     }
 
     /// Get all multi-aslan results
@@ -345,8 +741,92 @@ impl ASLANParser {
         self.multi_aslan_results.clone()
     }
 
-    /// Reset the parser state
+    /// The value returned by `parse`: every result with `multi_aslan_output`, else the current one
+    fn get_output(&self) -> Value {
+        if self.parser_settings.multi_aslan_output {
+            Value::Array(self.multi_aslan_results.clone())
+        } else {
+            self.get_result()
+        }
+    }
+
+    /// Get all schema violations reported so far
+    pub fn get_violations(&self) -> Vec<ASLANViolation> {
+        self.violations.clone()
+    }
+
+    /// Get every recovery from malformed input made so far. Recoveries never change the result
+    pub fn diagnostics(&self) -> Vec<ASLANDiagnostic> {
+        self.diagnostics.clone()
+    }
+
+    /// Split `input` into tokens as this parser reads it, with its settings and extensions.
+    /// Like `parse`, this consumes the input, so use a parser that has not parsed anything yet
+    pub fn lex(&mut self, input: &str) -> Vec<ASLANToken> {
+        let mut tokens = self.lex_next(input);
+        tokens.extend(self.lex_close());
+        tokens
+    }
+
+    /// Split `input` into tokens incrementally. Returns the tokens completed so far
+    pub(crate) fn lex_next(&mut self, input: &str) -> Vec<ASLANToken> {
+        self.tokens.get_or_insert_with(Vec::new);
+        for ch in input.chars() {
+            self.handle_next_char(ch);
+        }
+        self.take_tokens()
+    }
+
+    /// Return the remaining tokens at the end of the input
+    pub(crate) fn lex_close(&mut self) -> Vec<ASLANToken> {
+        self.tokens.get_or_insert_with(Vec::new);
+        // An unfinished delimiter at the end of the input
+        if self.is_building_delimiter() {
+            self.lex_malformed_delimiter(self.input_offset);
+        }
+        self.lex_text(self.input_offset);
+        self.take_tokens()
+    }
+
+    fn take_tokens(&mut self) -> Vec<ASLANToken> {
+        // Only keep the input of tokens that are not complete yet
+        self.lexed_input.drain(..self.lexed_until - self.lexed_input_start);
+        self.lexed_input_start = self.lexed_until;
+        self.tokens.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// The prefix detected with `detect_prefix`, once it formed a well-formed delimiter
+    pub fn detected_prefix(&self) -> Option<&str> {
+        self.detected_prefix.as_deref()
+    }
+
+    /// Get the input ranges of every value parsed so far, in order of first appearance.
+    /// Empty unless `track_spans` is enabled.
+    pub fn spans(&self) -> Vec<ASLANValueSpans> {
+        self.spans.clone()
+    }
+
+    /// Get the named args of the data delimiters of every field parsed so far.
+    /// Empty unless `named_args` is enabled.
+    pub fn annotations(&self) -> Vec<ASLANFieldAnnotations> {
+        self.annotations.clone()
+    }
+
+    /// Reset the parser to read a new input. Keeps its settings, event listeners and extensions
     pub fn reset(&mut self) {
+        let mut parser = Self::with_settings(self.parser_settings.clone());
+        parser.event_listeners = std::mem::take(&mut self.event_listeners);
+        parser.listener_idempotency_keys = std::mem::take(&mut self.listener_idempotency_keys);
+        parser.extensions = std::mem::take(&mut self.extensions);
+        *self = parser;
+    }
+
+    /// Start a new result, e.g. after a stop delimiter with multi-aslan output
+    fn reset_result(&mut self) {
+        self.closed = false;
+        self.coerced_result = None;
+        self.violations.clear();
+        self.reported_violations.clear();
         let default_field_name = self.parser_settings.default_field_name.clone();
         let initial_result = json!({ default_field_name.clone(): "" });
 
@@ -368,6 +848,8 @@ impl ASLANParser {
             already_seen_duplicate_keys: HashMap::new(),
             implicit_arrays: HashMap::new(),
             registered_instructions: Vec::new(),
+            opening_indent: 0,
+            child_indent: None,
         }];
     }
 
@@ -437,6 +919,28 @@ impl ASLANParser {
         self.event_listeners.end_data.push((key, Box::new(callback)));
     }
 
+    /// Add an event listener for schema violations
+    pub fn add_violation_listener<F>(&mut self, callback: F) -> String
+    where
+        F: FnMut(&ASLANViolation) + 'static,
+    {
+        let key = generate_random_idempotency_key();
+        self.add_violation_listener_with_key(key.clone(), callback);
+        key
+    }
+
+    /// Add a violation event listener with a specific idempotency key
+    pub fn add_violation_listener_with_key<F>(&mut self, key: String, callback: F)
+    where
+        F: FnMut(&ASLANViolation) + 'static,
+    {
+        if self.listener_idempotency_keys.contains(&key) {
+            return;
+        }
+        self.listener_idempotency_keys.insert(key.clone());
+        self.event_listeners.violation.push((key, Box::new(callback)));
+    }
+
     /// Remove a content event listener by key
     pub fn remove_content_listener(&mut self, key: &str) {
         self.listener_idempotency_keys.remove(key);
@@ -455,6 +959,62 @@ impl ASLANParser {
         self.event_listeners.end_data.retain(|(k, _)| k != key);
     }
 
+    /// Remove a violation event listener by key
+    pub fn remove_violation_listener(&mut self, key: &str) {
+        self.listener_idempotency_keys.remove(key);
+        self.event_listeners.violation.retain(|(k, _)| k != key);
+    }
+
+    /// Add an event listener for custom events emitted by extensions
+    pub fn add_extension_listener<F>(&mut self, callback: F) -> String
+    where
+        F: FnMut(&ASLANExtensionEvent) + 'static,
+    {
+        let key = generate_random_idempotency_key();
+        self.add_extension_listener_with_key(key.clone(), callback);
+        key
+    }
+
+    /// Add an extension event listener with a specific idempotency key
+    pub fn add_extension_listener_with_key<F>(&mut self, key: String, callback: F)
+    where
+        F: FnMut(&ASLANExtensionEvent) + 'static,
+    {
+        if self.listener_idempotency_keys.contains(&key) {
+            return;
+        }
+        self.listener_idempotency_keys.insert(key.clone());
+        self.event_listeners.extension.push((key, Box::new(callback)));
+    }
+
+    /// Remove an extension event listener
+    pub fn remove_extension_listener(&mut self, key: &str) {
+        self.listener_idempotency_keys.remove(key);
+        self.event_listeners.extension.retain(|(k, _)| k != key);
+    }
+
+    /// Handle delimiters with a custom suffix instead of dropping them as reserved
+    pub fn register_extension<E>(&mut self, extension: E) -> Result<(), ASLANSettingsError>
+    where
+        E: ASLANDelimiterExtension + 'static,
+    {
+        let suffix = extension.suffix();
+        if !suffix.is_ascii_alphanumeric() || "doiacepvgs".contains(suffix) {
+            return Err(ASLANSettingsError::new(
+                "extensions",
+                &format!("`{}` must be an alphanumeric suffix the spec does not define", suffix),
+            ));
+        }
+        if self.extensions.iter().any(|registered| registered.suffix() == suffix) {
+            return Err(ASLANSettingsError::new(
+                "extensions",
+                &format!("an extension for `{}` is already registered", suffix),
+            ));
+        }
+        self.extensions.push(Box::new(extension));
+        Ok(())
+    }
+
     /// Clear all event listeners
     pub fn clear_event_listeners(&mut self) {
         self.listener_idempotency_keys.clear();
@@ -504,15 +1064,195 @@ impl ASLANParser {
         self.recent_delimiters.get_nth_most_recent_not_in(2, &excluded)
     }
 
+    /// The input range of the delimiter in `delimiter_buffer`, which the current char completes
+    fn delimiter_span(&self) -> ASLANSpan {
+        ASLANSpan {
+            start: self.input_offset - self.delimiter_buffer.len() - 1,
+            end: self.input_offset,
+        }
+    }
+
+    fn get_spans_at_current_key_mut(&mut self) -> &mut ASLANValueSpans {
+        let path = self.get_schema_path();
+        let multi_aslan_index = self.multi_aslan_results.len().saturating_sub(1);
+        let position = self
+            .spans
+            .iter()
+            .rposition(|spans| spans.multi_aslan_index == multi_aslan_index && spans.path == path);
+        let position = position.unwrap_or_else(|| {
+            self.spans.push(ASLANValueSpans {
+                path,
+                multi_aslan_index,
+                delimiters: Vec::new(),
+                content: Vec::new(),
+            });
+            self.spans.len() - 1
+        });
+        &mut self.spans[position]
+    }
+
+    fn get_spans_at_current_key(&self) -> Option<ASLANValueSpans> {
+        if !self.parser_settings.track_spans {
+            return None;
+        }
+        let path = self.get_schema_path();
+        let multi_aslan_index = self.multi_aslan_results.len().saturating_sub(1);
+        self.spans
+            .iter()
+            .rev()
+            .find(|spans| spans.multi_aslan_index == multi_aslan_index && spans.path == path)
+            .cloned()
+    }
+
+    fn record_delimiter_span(&mut self, span: ASLANSpan) {
+        if self.parser_settings.track_spans {
+            self.get_spans_at_current_key_mut().delimiters.push(span);
+        }
+    }
+
+    fn record_content_span(&mut self, span: ASLANSpan) {
+        if !self.parser_settings.track_spans || span.start == span.end {
+            return;
+        }
+        let content = &mut self.get_spans_at_current_key_mut().content;
+        match content.last_mut() {
+            Some(last) if last.end == span.start => last.end = span.end,
+            _ => content.push(span),
+        }
+    }
+
+    /// Forget the content of the value at the current key and everything nested in it
+    fn clear_spans_under_current_key(&mut self) {
+        if !self.parser_settings.track_spans {
+            return;
+        }
+        let path = self.get_schema_path();
+        let multi_aslan_index = self.multi_aslan_results.len().saturating_sub(1);
+        self.spans.retain_mut(|spans| {
+            if spans.multi_aslan_index != multi_aslan_index || !spans.path.starts_with(&path) {
+                return true;
+            }
+            spans.content.clear();
+            spans.path.len() == path.len()
+        });
+    }
+
     fn exit_delimiter_into_data(&mut self, ch: char) {
+        if self.detecting_prefix {
+            // The candidate prefix did not form a delimiter, so this is text
+            self.delimiter_open_substrings.clear();
+        }
+        self.delimiter_repairs.clear();
+        let end = self.input_offset - ch.len_utf8();
+        if self.current_escape_delimiter.is_none() && self.has_complete_open_substring() {
+            let message = format!("`{}` is not a valid delimiter and is kept as text", self.delimiter_buffer);
+            self.report_diagnostic(ASLANDiagnosticCode::MalformedDelimiter, end - self.delimiter_buffer.len(), &message);
+        }
+        self.lex_malformed_delimiter(end);
         self.current_value.push_str(&self.delimiter_buffer);
-        self.current_value.push(ch);
         self.delimiter_buffer.clear();
         self.current_delimiter = None;
         self.state = ASLANParserState::Data;
+        if ch == '[' && !self.parsing_locked {
+            // Spec: Every `[` starts buffering a possible delimiter (Implementation specifics), so the
+            // char that broke this one may start the next, e.g. `[x[asland_y]`
+            self.store_current_value_ending_at(Some(self.input_offset - ch.len_utf8()));
+            self.state = ASLANParserState::MaybeDelimiter;
+            self.delimiter_buffer.push(ch);
+            return;
+        }
+        self.current_value.push(ch);
+        if !self.parsing_locked {
+            self.store_current_value();
+        }
+    }
+
+    /// Pass `ch` to the state machine, holding back input that may be a code fence
+    fn feed_char(&mut self, ch: char) {
+        if self.opening_fence_pending {
+            self.fence_buffer.push(ch);
+            if !is_opening_fence_prefix(&self.fence_buffer) {
+                self.opening_fence_pending = false;
+                self.flush_fence_buffer();
+            } else if ch == '\n' && self.fence_buffer.contains("```") {
+                self.opening_fence_pending = false;
+                self.awaiting_opening_fence = false;
+                self.inside_code_fences = true;
+                self.discard_fence_buffer();
+            }
+            return;
+        }
+        if !self.inside_code_fences {
+            self.handle_next_char(ch);
+            // Text like `Here you go:` may come before the fence line
+            if ch == '\n' && self.awaiting_opening_fence {
+                self.opening_fence_pending = true;
+            }
+            return;
+        }
+        if self.fence_buffer.is_empty() {
+            // The closing fence starts on its own line, and never inside an escape
+            if ch == '\n' && self.current_escape_delimiter.is_none() {
+                self.fence_buffer.push(ch);
+            } else {
+                self.handle_next_char(ch);
+            }
+            return;
+        }
+        self.fence_buffer.push(ch);
+        if !is_closing_fence_prefix(&self.fence_buffer) {
+            self.fence_buffer.pop();
+            self.flush_fence_buffer();
+            self.feed_char(ch);
+        }
+    }
+
+    /// At the end of the input, discard a held back fence
+    fn finish_code_fence(&mut self) {
+        self.opening_fence_pending = false;
+        if self.fence_buffer.contains("```") {
+            self.discard_fence_buffer();
+        } else {
+            self.flush_fence_buffer();
+        }
+    }
+
+    fn flush_fence_buffer(&mut self) {
+        for ch in std::mem::take(&mut self.fence_buffer).chars() {
+            self.handle_next_char(ch);
+        }
+    }
+
+    fn discard_fence_buffer(&mut self) {
+        // Offsets keep counting discarded input, so spans and diagnostics refer to the original text
+        self.input_offset += self.fence_buffer.len();
+        self.fence_buffer.clear();
     }
 
     fn handle_next_char(&mut self, ch: char) {
+        self.closed = false;
+        self.coerced_result = None;
+        self.input_offset += ch.len_utf8();
+        self.track_line_indent(ch);
+        let state = self.state;
+        if self.tokens.is_some() {
+            self.lexed_input.push(ch);
+        }
+        self.dispatch(ch);
+        if self.tokens.is_some() && !self.is_building_delimiter() {
+            let kind = if state == ASLANParserState::Comment && self.state == ASLANParserState::Comment {
+                ASLANTokenKind::CommentBody
+            } else {
+                self.text_token_kind()
+            };
+            self.push_token(kind, self.input_offset);
+        }
+    }
+
+    fn dispatch(&mut self, ch: char) {
+        if self.handle_escaped_closing_bracket(ch) {
+            return;
+        }
         match self.state {
             ASLANParserState::GoDelimiter => self.handle_go_delimiter(ch),
             ASLANParserState::StopDelimiter => self.handle_stop_delimiter(ch),
@@ -522,6 +1262,7 @@ impl ASLANParser {
             ASLANParserState::MaybeDelimiter => self.handle_maybe_delimiter(ch),
             ASLANParserState::Delimiter => self.handle_delimiter(ch),
             ASLANParserState::ReservedDelimiter => self.handle_reserved_delimiter(ch),
+            ASLANParserState::ExtensionDelimiter => self.handle_extension_delimiter(ch),
             ASLANParserState::Object => self.handle_object(ch),
             ASLANParserState::Array => self.handle_array(ch),
             ASLANParserState::Comment => self.handle_comment(ch),
@@ -544,8 +1285,210 @@ impl ASLANParser {
         }
     }
 
+    fn track_line_indent(&mut self, ch: char) {
+        match ch {
+            '\n' => {
+                self.at_line_start = true;
+                self.line_indent = 0;
+            }
+            ' ' | '\t' if self.at_line_start => self.line_indent += 1,
+            _ if self.at_line_start => {
+                self.at_line_start = false;
+                self.line_content_start = self.input_offset - ch.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    /// With `repair_indentation`, close the blocks a data delimiter starting at `start` is
+    /// indented out of, then remember the indentation of the fields of the current block
+    fn close_blocks_by_indentation(&mut self, start: usize) {
+        if !self.parser_settings.repair_indentation || start != self.line_content_start {
+            return;
+        }
+        let indent = self.line_indent;
+        while self.stack.len() > 1 {
+            let frame = self.stack.last().unwrap();
+            let indented_out = frame.child_indent.is_some_and(|child| child > frame.opening_indent) && indent <= frame.opening_indent;
+            if !indented_out {
+                break;
+            }
+            let kind = if frame.inner_result.is_array() { "array" } else { "object" };
+            self.pop_block();
+            let message = format!("{} is closed by the indentation of the next field", kind);
+            self.report_diagnostic(ASLANDiagnosticCode::InferredClose, start, &message);
+        }
+        if self.stack.len() > 1 {
+            let frame = self.stack.last_mut().unwrap();
+            frame.child_indent.get_or_insert(indent);
+        }
+    }
+
+    fn is_building_delimiter(&self) -> bool {
+        !matches!(
+            self.state,
+            ASLANParserState::Start
+                | ASLANParserState::Object
+                | ASLANParserState::Array
+                | ASLANParserState::Comment
+                | ASLANParserState::Escape
+                | ASLANParserState::Data
+                | ASLANParserState::Go
+                | ASLANParserState::Stop
+                | ASLANParserState::Locked
+        )
+    }
+
+    fn text_token_kind(&self) -> ASLANTokenKind {
+        if self.current_escape_delimiter.is_some() {
+            ASLANTokenKind::EscapeBody
+        } else {
+            ASLANTokenKind::Text
+        }
+    }
+
+    /// Add a token covering the input from the end of the last token to `end`
+    fn push_token(&mut self, kind: ASLANTokenKind, end: usize) {
+        let start = self.lexed_until;
+        let tokens = match &mut self.tokens {
+            Some(tokens) if end > start => tokens,
+            _ => return,
+        };
+        self.lexed_until = end;
+        let text = &self.lexed_input[start - self.lexed_input_start..end - self.lexed_input_start];
+        if let Some(last) = tokens.last_mut() {
+            let mergeable = matches!(
+                kind,
+                ASLANTokenKind::Text | ASLANTokenKind::EscapeBody | ASLANTokenKind::CommentBody
+            );
+            if mergeable && last.kind == kind {
+                last.span.end = end;
+                last.text.push_str(text);
+                return;
+            }
+        }
+        tokens.push(ASLANToken {
+            kind,
+            span: ASLANSpan { start, end },
+            text: text.to_string(),
+        });
+    }
+
+    fn lex_text(&mut self, end: usize) {
+        self.push_token(self.text_token_kind(), end);
+    }
+
+    /// Emit the delimiter in `delimiter_buffer` as malformed when it got past the prefix
+    fn lex_malformed_delimiter(&mut self, end: usize) {
+        if self.tokens.is_none()
+            || self.current_escape_delimiter.is_some()
+            || !self.has_complete_open_substring()
+        {
+            return;
+        }
+        self.lex_text(end - self.delimiter_buffer.len());
+        self.push_token(ASLANTokenKind::MalformedDelimiter, end);
+    }
+
+    fn lex_delimiter(&mut self, span: ASLANSpan) {
+        if self.tokens.is_none() {
+            return;
+        }
+        self.lex_text(span.start);
+        let delimiter = self.current_delimiter.clone();
+        let prefix = delimiter.as_ref().and_then(|d| d.prefix.clone()).unwrap_or_default();
+        if let Some(ASLANDelimiterData { extension: Some(suffix), content, args, .. }) = delimiter {
+            let kind = ASLANTokenKind::ExtensionDelimiter { prefix, suffix, content, args };
+            return self.push_token(kind, span.end);
+        }
+        let kind = match delimiter.as_ref().and_then(|d| d.suffix.map(|suffix| (d, suffix))) {
+            Some((delimiter, delimiter_type)) => ASLANTokenKind::Delimiter {
+                prefix,
+                delimiter_type,
+                content: delimiter.content.clone(),
+                args: delimiter.args.clone(),
+                block: None,
+            },
+            None => ASLANTokenKind::ReservedDelimiter {
+                prefix,
+                suffix: self.delimiter_buffer.chars().last().unwrap_or_default(),
+            },
+        };
+        self.push_token(kind, span.end);
+    }
+
+    fn lex_block_edge(&mut self, edge: ASLANBlockEdge) {
+        if let Some(ASLANToken {
+            kind: ASLANTokenKind::Delimiter { block, .. },
+            ..
+        }) = self.tokens.as_mut().and_then(|tokens| tokens.last_mut())
+        {
+            *block = Some(edge);
+        }
+    }
+
+    /// The delimiter in `delimiter_buffer` is valid. Returns its input range
+    fn complete_delimiter(&mut self) -> ASLANSpan {
+        self.awaiting_opening_fence = false;
+        if self.detecting_prefix {
+            self.detecting_prefix = false;
+            self.detected_prefix = self.current_delimiter.as_ref().and_then(|d| d.prefix.clone());
+        }
+        let span = self.delimiter_span();
+        self.lex_delimiter(span);
+        if !self.delimiter_repairs.is_empty() {
+            let message = format!("`{}]` is read as a delimiter despite {}", self.delimiter_buffer, self.delimiter_repairs.join(", "));
+            self.delimiter_repairs.clear();
+            self.report_diagnostic(ASLANDiagnosticCode::RepairedDelimiter, span.start, &message);
+        }
+        span
+    }
+
+    /// With `tolerance.escaped_brackets`, `\` may start a delimiter written as `\[...\]`
+    fn start_escaped_bracket(&mut self, ch: char) -> bool {
+        if ch != '\\'
+            || !self.parser_settings.tolerance.escaped_brackets
+            || self.current_escape_delimiter.is_some()
+            || self.detecting_prefix
+        {
+            return false;
+        }
+        self.state = ASLANParserState::MaybeDelimiter;
+        self.delimiter_buffer.push(ch);
+        true
+    }
+
+    /// Take the `\` of a closing `\]` when the delimiter opened with `\[`
+    fn handle_escaped_closing_bracket(&mut self, ch: char) -> bool {
+        if !self.delimiter_buffer.starts_with("\\[") || !self.is_building_delimiter() || !self.has_complete_open_substring() {
+            return false;
+        }
+        if self.delimiter_buffer.ends_with('\\') {
+            if ch == ']' {
+                return false;
+            }
+            self.exit_delimiter_into_data(ch);
+            return true;
+        }
+        if ch == '\\' {
+            self.delimiter_buffer.push(ch);
+            return true;
+        }
+        false
+    }
+
+    /// `delimiter_buffer` without the `\` of an escaped bracket
+    fn delimiter_text(&self) -> &str {
+        self.delimiter_buffer.strip_prefix('\\').unwrap_or(&self.delimiter_buffer)
+    }
+
     fn handle_locked(&mut self, ch: char) {
         if ch == '[' {
+            if self.detecting_prefix {
+                // A candidate prefix that did not form a delimiter before locking
+                self.delimiter_open_substrings.clear();
+            }
+            self.delimiter_repairs.clear();
             self.state = ASLANParserState::MaybeDelimiter;
             self.delimiter_buffer = ch.to_string();
         }
@@ -555,13 +1498,14 @@ impl ASLANParser {
         if ch == ']' {
             // Spec: Go delimiters have no <CONTENT> or args
             // VALID GO DELIMITER
+            self.complete_delimiter();
             self.state = ASLANParserState::Go;
             self.delimiter_buffer.clear();
             self.current_value.clear();
             self.parsing_locked = false;
             if self.parser_settings.strict_start && !self.did_stop {
                 self.close();
-                self.reset();
+                self.reset_result();
                 self.multi_aslan_results.push(self.stack[0].inner_result.clone());
             }
             self.did_stop = false;
@@ -576,6 +1520,7 @@ impl ASLANParser {
         if ch == ']' {
             // Spec: Stop delimiters have no <CONTENT> or args
             // VALID STOP DELIMITER
+            self.complete_delimiter();
             self.state = ASLANParserState::Stop;
             self.delimiter_buffer.clear();
             self.current_value.clear();
@@ -584,7 +1529,7 @@ impl ASLANParser {
                     self.parsing_locked = true;
                 }
                 self.close();
-                self.reset();
+                self.reset_result();
                 self.multi_aslan_results.push(self.stack[0].inner_result.clone());
                 self.state = ASLANParserState::Start;
                 self.did_stop = true;
@@ -600,42 +1545,118 @@ impl ASLANParser {
         if ch == '[' {
             self.state = ASLANParserState::MaybeDelimiter;
             self.delimiter_buffer.push(ch);
-        } else {
+        } else if !self.start_escaped_bracket(ch) {
             self.state = ASLANParserState::Data;
             self.current_value.push(ch);
         }
     }
 
     fn handle_maybe_delimiter(&mut self, ch: char) {
-        if self.delimiter_buffer.len() > self.delimiter_open_substring.len() {
-            self.state = ASLANParserState::Data;
-            self.current_value.push(ch);
-            return;
-        }
-        let expected_char = self.delimiter_open_substring.chars().nth(self.delimiter_buffer.len());
-        if expected_char == Some(ch) {
+        if self.detecting_prefix && self.delimiter_open_substrings.is_empty() {
+            return self.detect_prefix(ch);
+        }
+        let text = self.delimiter_text();
+        let ignore_case = self.parser_settings.tolerance.ignore_case;
+        let continues_prefix = self.delimiter_open_substrings.iter().any(|open| {
+            let next = open.get(text.len()..).and_then(|rest| rest.chars().next());
+            open.get(..text.len()).is_some_and(|start| start == text || (ignore_case && start.eq_ignore_ascii_case(text)))
+                && next.is_some_and(|next| next == ch || (ignore_case && next.eq_ignore_ascii_case(&ch)))
+        });
+        if continues_prefix {
             self.delimiter_buffer.push(ch);
-            if self.delimiter_buffer == self.delimiter_open_substring {
+            if self.has_complete_open_substring() {
                 self.state = ASLANParserState::Delimiter;
+                if self.delimiter_buffer.starts_with('\\') {
+                    self.delimiter_repairs.push("escaped brackets");
+                }
+                let text = self.delimiter_text();
+                if !self.delimiter_open_substrings.iter().any(|open| open == text) {
+                    self.delimiter_repairs.push("the case of the prefix");
+                }
             }
             return;
         }
         self.exit_delimiter_into_data(ch);
     }
 
+    /// Collect `[` and alphanumeric chars until a char shows where the prefix ends, e.g. the `_`
+    /// of `[llmd_`. A matching candidate prefix is used until the delimiter turns out malformed
+    fn detect_prefix(&mut self, ch: char) {
+        if ch.is_ascii_alphanumeric() {
+            self.delimiter_buffer.push(ch);
+            return;
+        }
+        let candidate = &self.delimiter_buffer[1..];
+        let suffix = candidate.chars().last();
+        let prefix = &candidate[..candidate.len().saturating_sub(1)];
+        let allowed_prefixes = &self.parser_settings.allowed_prefixes;
+        // Without an allow-list, only data and go delimiters are trusted, as `[todo]` looks like an
+        // object delimiter
+        let well_formed_start = match suffix {
+            Some('d') => ch == '_',
+            Some('g') if self.parser_settings.strict_start => ch == ']',
+            Some('i' | 'e') if !allowed_prefixes.is_empty() => ch == '_',
+            Some('g' | 's' | 'o' | 'a' | 'v' | 'p' | 'c') if !allowed_prefixes.is_empty() => ch == ']',
+            _ => false,
+        };
+        if prefix.is_empty()
+            || !well_formed_start
+            || (!allowed_prefixes.is_empty() && !allowed_prefixes.iter().any(|allowed| allowed == prefix))
+        {
+            return self.exit_delimiter_into_data(ch);
+        }
+        let suffix = suffix.unwrap();
+        self.delimiter_buffer = format!("[{}", prefix);
+        self.delimiter_open_substrings = vec![self.delimiter_buffer.clone()];
+        self.state = ASLANParserState::Delimiter;
+        self.handle_delimiter(suffix);
+        self.dispatch(ch);
+    }
+
+    /// Whether `delimiter_buffer` starts with `[` and one of the prefixes
+    fn has_complete_open_substring(&self) -> bool {
+        self.matched_open_substring().is_some()
+    }
+
+    /// The open substring `delimiter_buffer` starts with, allowing for the tolerance rules
+    fn matched_open_substring(&self) -> Option<&str> {
+        let text = self.delimiter_text();
+        let ignore_case = self.parser_settings.tolerance.ignore_case;
+        self.delimiter_open_substrings
+            .iter()
+            .map(|open| open.as_str())
+            .find(|open| text.get(..open.len()).is_some_and(|start| start == *open || (ignore_case && start.eq_ignore_ascii_case(open))))
+    }
+
     fn handle_delimiter(&mut self, ch: char) {
         if self.parsing_locked && ch != 'g' && !self.parser_settings.strict_start {
             self.state = ASLANParserState::Locked;
             return;
         }
+        let tolerance = &self.parser_settings.tolerance;
+        let open = self.matched_open_substring().unwrap_or_default().to_string();
+        let at_prefix_end = self.delimiter_text().len() == open.len();
+        if at_prefix_end && ((ch == ' ' && tolerance.space_after_prefix) || (ch == '_' && tolerance.underscore_after_prefix)) {
+            self.delimiter_repairs.push(if ch == ' ' { "a space after the prefix" } else { "an underscore after the prefix" });
+            self.delimiter_buffer.push(ch);
+            return;
+        }
+        let suffix = if tolerance.ignore_case && ch.is_ascii_uppercase() {
+            self.delimiter_repairs.push("the case of the suffix");
+            ch.to_ascii_lowercase()
+        } else {
+            ch
+        };
         self.current_delimiter = Some(ASLANDelimiterData {
-            prefix: Some(self.parser_settings.prefix.clone()),
+            prefix: Some(open[1..].to_string()),
             suffix: None,
             content: None,
             args: Vec::new(),
+            extension: None,
+            arg_quoting: ASLANArgQuoting::Plain,
         });
 
-        match ch {
+        match suffix {
             'd' => {
                 self.state = ASLANParserState::DataDelimiter;
                 self.current_delimiter.as_mut().unwrap().suffix = Some(ASLANDelimiterType::Data);
@@ -686,6 +1707,12 @@ impl ASLANParser {
                 self.current_delimiter.as_mut().unwrap().suffix = Some(ASLANDelimiterType::Stop);
                 self.delimiter_buffer.push(ch);
             }
+            _ if self.extensions.iter().any(|extension| extension.suffix() == suffix) => {
+                self.state = ASLANParserState::ExtensionDelimiter;
+                self.current_delimiter.as_mut().unwrap().extension = Some(suffix);
+                self.delimiter_buffer.push(ch);
+                return;
+            }
             _ => {
                 if ch.is_ascii_alphanumeric() {
                     self.state = ASLANParserState::ReservedDelimiter;
@@ -714,11 +1741,103 @@ impl ASLANParser {
             // INVALID RESERVED DELIMITER
             return self.exit_delimiter_into_data(ch);
         }
+        let span = self.complete_delimiter();
+        let message = format!("`{}]` has a reserved suffix and is dropped", self.delimiter_buffer);
+        self.report_diagnostic(ASLANDiagnosticCode::ReservedDelimiter, span.start, &message);
         self.delimiter_buffer.clear();
         self.state = ASLANParserState::Data;
         self.current_value.clear();
     }
 
+    fn handle_extension_delimiter(&mut self, ch: char) {
+        if self.parsing_locked {
+            self.state = ASLANParserState::Locked;
+            return;
+        }
+        if self.current_escape_delimiter.is_some() {
+            return self.exit_delimiter_into_data(ch);
+        }
+        let delimiter = self.current_delimiter.as_ref().unwrap();
+        let suffix = delimiter.extension.unwrap();
+        let grammar = match self.extensions.iter().find(|extension| extension.suffix() == suffix) {
+            Some(extension) => extension.grammar(),
+            None => return self.exit_delimiter_into_data(ch),
+        };
+        if !delimiter.args.is_empty() {
+            match self.read_arg_char(ch) {
+                ASLANArgChar::Consumed => return,
+                ASLANArgChar::Invalid => return self.exit_delimiter_into_data(ch),
+                ASLANArgChar::Separator => {}
+            }
+        }
+        let delimiter = self.current_delimiter.as_ref().unwrap();
+        let content = delimiter.content.clone();
+        let in_args = !delimiter.args.is_empty();
+        let content_ok = match &content {
+            Some(content) => !content.is_empty() && !content.ends_with('_'),
+            None => grammar.content != ASLANExtensionContent::Required,
+        };
+        let delimiter = self.current_delimiter.as_mut().unwrap();
+        match ch {
+            ']' if in_args || content_ok => {
+                // VALID EXTENSION DELIMITER
+                self.state = ASLANParserState::Data;
+                self.complete_delimiter();
+                self.delimiter_buffer.clear();
+                self.current_value.clear();
+                let delimiter = self.current_delimiter.as_ref().unwrap();
+                let delimiter = ASLANExtensionDelimiter {
+                    suffix,
+                    content: delimiter.content.clone(),
+                    args: delimiter.args.clone(),
+                };
+                self.run_extension(delimiter);
+                return;
+            }
+            ':' if in_args || (grammar.args && content_ok) => delimiter.args.push(String::new()),
+            '_' if content.is_none() && grammar.content != ASLANExtensionContent::Forbidden => {
+                delimiter.content = Some(String::new());
+            }
+            _ if content.is_some() && (ch.is_ascii_alphanumeric() || (ch == '_' && content != Some(String::new()))) => {
+                delimiter.content.as_mut().unwrap().push(ch);
+            }
+            // INVALID EXTENSION DELIMITER
+            _ => return self.exit_delimiter_into_data(ch),
+        }
+        self.delimiter_buffer.push(ch);
+    }
+
+    fn run_extension(&mut self, delimiter: ASLANExtensionDelimiter) {
+        let Some(index) = self.extensions.iter().position(|extension| extension.suffix() == delimiter.suffix) else {
+            return;
+        };
+        let field_name = self.get_current_key_string();
+        let value = self.get_value_at_key(&field_name).cloned();
+        let mut context = ASLANExtensionContext {
+            field_name: field_name.clone(),
+            path: self.get_current_path(),
+            value: value.clone(),
+            events: Vec::new(),
+        };
+        self.extensions[index].handle(&delimiter, &mut context);
+        if context.value != value {
+            if let Some(value) = context.value {
+                self.set_value_at_key(&field_name, value);
+            }
+        }
+        for data in context.events {
+            let event = ASLANExtensionEvent {
+                suffix: delimiter.suffix,
+                field_name: field_name.clone(),
+                path: context.path.clone(),
+                data,
+            };
+            for (_, handler) in &mut self.event_listeners.extension {
+                handler(&event);
+            }
+        }
+    }
+
     fn handle_object_delimiter(&mut self, ch: char) {
         if self.parsing_locked {
             self.state = ASLANParserState::Locked;
@@ -731,17 +1850,23 @@ impl ASLANParser {
             // Spec: Object delimiters have no <CONTENT> or args
             // VALID OBJECT DELIMITER
             self.state = ASLANParserState::Object;
+            let span = self.complete_delimiter();
             self.delimiter_buffer.clear();
 
+            // Let the schema decide when it describes the current key
+            if let Some(expects_object) = self.schema_expects_block_at_current_key(ASLANSchemaType::Object) {
+                if expects_object && self.can_open_block_at_current_key() {
+                    self.create_new_object(span);
+                } else {
+                    self.close_block(span);
+                }
+                return;
+            }
+
             // Check if at max object depth - always close, never create deeper nesting
             if let Some(max_depth) = self.parser_settings.max_object_depth {
                 if self.get_object_depth() >= max_depth {
-                    if self.stack.len() > 1 {
-                        self.emit_end_events_if_required();
-                        self.emit_end_data_events_if_required();
-                        self.sync_stack_to_root();
-                        self.stack.pop();
-                    }
+                    self.close_block(span);
                     return;
                 }
             }
@@ -756,21 +1881,16 @@ impl ASLANParser {
                     let already_seen = self.stack.last().unwrap().already_seen_duplicate_keys.get(&current_key).copied().unwrap_or(false);
                     if already_seen {
                         self.stack.last_mut().unwrap().already_seen_duplicate_keys.insert(current_key, false);
-                        self.create_new_object();
+                        self.create_new_object(span);
                         return;
                     }
-                    if self.stack.len() > 1 {
-                        self.emit_end_events_if_required();
-                        self.emit_end_data_events_if_required();
-                        self.sync_stack_to_root();
-                        self.stack.pop();
-                    }
+                    self.close_block(span);
                 } else {
-                    self.create_new_object();
+                    self.create_new_object(span);
                 }
                 return;
             }
-            self.create_new_object();
+            self.create_new_object(span);
             return;
         }
         // Spec: Object delimiters have no <CONTENT> or args
@@ -778,6 +1898,142 @@ impl ASLANParser {
         self.exit_delimiter_into_data(ch);
     }
 
+    fn close_block(&mut self, span: ASLANSpan) {
+        self.lex_block_edge(ASLANBlockEdge::Close);
+        if self.stack.len() == 1 {
+            self.report_diagnostic(ASLANDiagnosticCode::ExtraneousClose, span.start, "no object or array is open");
+        }
+        if self.stack.len() > 1 {
+            self.pop_block();
+            self.record_delimiter_span(span);
+        }
+    }
+
+    fn pop_block(&mut self) {
+        self.emit_end_events_if_required();
+        self.emit_end_data_events_if_required();
+        self.sync_stack_to_root();
+        self.stack.pop();
+    }
+
+    fn get_schema_path(&self) -> Vec<String> {
+        self.stack.iter().map(|frame| frame.current_key.as_string()).collect()
+    }
+
+    /// Whether the schema expects a block of `block_type` at the current key.
+    /// Returns `None` when there is no schema or it doesn't describe the current key.
+    fn schema_expects_block_at_current_key(&self, block_type: ASLANSchemaType) -> Option<bool> {
+        let schema = self.parser_settings.schema.as_ref()?;
+        let node = schema.resolve(&self.get_schema_path())?;
+        Some(node.schema_type == block_type)
+    }
+
+    fn get_schema_for_current_frame(&self) -> Option<&ASLANSchema> {
+        let path = self.get_schema_path();
+        self.parser_settings.schema.as_ref()?.resolve(&path[..path.len() - 1])
+    }
+
+    fn get_schema_for_current_key(&self) -> Option<&ASLANSchema> {
+        self.parser_settings.schema.as_ref()?.resolve(&self.get_schema_path())
+    }
+
+    fn check_current_key_against_schema(&mut self) {
+        let frame_schema = match self.get_schema_for_current_frame() {
+            Some(frame_schema) => frame_schema,
+            None => return,
+        };
+        match (frame_schema.schema_type, self.get_current_key()) {
+            (ASLANSchemaType::Object, ASLANKey::String(key))
+                if !frame_schema.additional_fields
+                    && key != &self.parser_settings.default_field_name
+                    && frame_schema.get_field(key).is_none() =>
+            {
+                let message = format!("field `{}` is not declared in the schema", key);
+                let path = self.get_schema_path();
+                self.report_violation(schema::violation(ASLANViolationKind::UnknownField, &path, &message));
+            }
+            (ASLANSchemaType::Array, ASLANKey::Index(index))
+                if frame_schema.max_items.map(|max| *index >= max as i64).unwrap_or(false) =>
+            {
+                let max_items = frame_schema.max_items.unwrap_or_default();
+                let message = format!("expected at most {} items but found {}", max_items, index + 1);
+                let path = self.get_schema_path();
+                let path = &path[..path.len() - 1];
+                self.report_violation(schema::violation(ASLANViolationKind::TooManyItems, path, &message));
+            }
+            _ => {}
+        }
+    }
+
+    fn report_violation_at_current_key(&mut self, kind: ASLANViolationKind, message: &str) {
+        let path = self.get_schema_path();
+        self.report_violation(schema::violation(kind, &path, message));
+    }
+
+    fn report_diagnostic(&mut self, code: ASLANDiagnosticCode, offset: usize, message: &str) {
+        self.diagnostics.push(ASLANDiagnostic {
+            code,
+            severity: code.severity(),
+            message: message.to_string(),
+            offset,
+            path: self.get_schema_path(),
+        });
+    }
+
+    fn report_violation(&mut self, violation: ASLANViolation) {
+        // Only report the first violation of a kind at a path per ASLAN result
+        if !self.reported_violations.insert((violation.kind, violation.path.clone())) {
+            return;
+        }
+        for (_, handler) in &mut self.event_listeners.violation {
+            handler(&violation);
+        }
+        self.violations.push(violation);
+    }
+
+    fn coerce_result_leaves(&mut self) {
+        if !self.parser_settings.coerce_leaves {
+            return;
+        }
+        // Coerce a copy, so input streamed after `close` still appends to the raw text
+        let mut result = self.stack[0].inner_result.clone();
+        let violations = match &self.parser_settings.schema {
+            Some(schema) => schema.coerce(&mut result),
+            None => return,
+        };
+        if let Some(last) = self.multi_aslan_results.last_mut() {
+            *last = result.clone();
+        }
+        self.coerced_result = Some(result);
+        for violation in violations {
+            self.report_violation(violation);
+        }
+    }
+
+    fn validate_result_against_schema(&mut self) {
+        let violations = match &self.parser_settings.schema {
+            Some(schema) => schema.validate_with_default_field(
+                self.coerced_result.as_ref().unwrap_or(&self.stack[0].inner_result),
+                &self.parser_settings.default_field_name,
+            ),
+            None => return,
+        };
+        for violation in violations {
+            self.report_violation(violation);
+        }
+    }
+
+    fn can_open_block_at_current_key(&mut self) -> bool {
+        let current_key = self.get_current_key_string();
+        let frame = self.stack.last_mut().unwrap();
+        // Spec: Duplicate fields with non-primitive values always take the last value
+        if frame.already_seen_duplicate_keys.get(&current_key).copied().unwrap_or(false) {
+            frame.already_seen_duplicate_keys.insert(current_key, false);
+            return true;
+        }
+        !self.get_value_at_key(&current_key).map(|v| v.is_object() || v.is_array()).unwrap_or(false)
+    }
+
     fn get_object_safe_latest_result(&self) -> bool {
         let current_key = self.get_current_key_string();
         if let Some(value) = self.get_value_at_key(&current_key) {
@@ -803,9 +2059,13 @@ impl ASLANParser {
         }
     }
 
-    fn create_new_object(&mut self) {
+    fn create_new_object(&mut self, span: ASLANSpan) {
         self.current_value.clear();
         let current_key = self.get_current_key_string();
+        // Whatever was stored at this key before is replaced
+        self.clear_spans_under_current_key();
+        self.record_delimiter_span(span);
+        self.lex_block_edge(ASLANBlockEdge::Open);
         
         // Set the value to an empty object
         let latest = self.get_latest_result_mut();
@@ -833,6 +2093,8 @@ impl ASLANParser {
             already_seen_duplicate_keys: HashMap::new(),
             implicit_arrays: HashMap::new(),
             registered_instructions: Vec::new(),
+            opening_indent: self.line_indent,
+            child_indent: None,
         });
     }
 
@@ -857,6 +2119,13 @@ impl ASLANParser {
             self.current_value.clear();
             return;
         }
+        if ch.is_ascii_alphanumeric() && self.parser_settings.tolerance.missing_underscore {
+            self.delimiter_repairs.push("a missing underscore before the name");
+            self.state = ASLANParserState::InstructionDelimiterName;
+            self.current_delimiter.as_mut().unwrap().content = Some(String::new());
+            self.current_value.clear();
+            return self.handle_instruction_delimiter_name(ch);
+        }
         // Spec: Instruction delimiters must contain <CONTENT>
         // INVALID INSTRUCTION DELIMITER
         self.exit_delimiter_into_data(ch);
@@ -898,6 +2167,8 @@ impl ASLANParser {
             // VALID INSTRUCTION DELIMITER
             let (index, part_index) = self.get_instruction_indices();
             self.state = ASLANParserState::Data;
+            let span = self.complete_delimiter();
+            self.record_delimiter_span(span);
             
             let current_key = self.get_current_key_string();
             let already_seen = self.stack.last().unwrap().already_seen_duplicate_keys.get(&current_key).copied().unwrap_or(false);
@@ -906,12 +2177,16 @@ impl ASLANParser {
             if !already_seen || !is_keep_first {
                 let args = self.current_delimiter.as_ref().unwrap().args.clone();
                 let content = self.current_delimiter.as_ref().unwrap().content.clone().unwrap_or_default();
+                let named_args = self.named_args(&args);
                 self.register_instruction(ASLANRegisteredInstruction {
                     name: content,
                     index,
                     args,
                     key: current_key.clone(),
                     part_index,
+                    named_args,
+                    ranged: false,
+                    end: None,
                 });
                 
                 let is_object = self.get_value_at_key(&current_key).map(|v| v.is_object() || v.is_array()).unwrap_or(false);
@@ -941,11 +2216,18 @@ impl ASLANParser {
             self.state = ASLANParserState::Locked;
             return;
         }
+        match self.read_arg_char(ch) {
+            ASLANArgChar::Consumed => return,
+            ASLANArgChar::Invalid => return self.exit_delimiter_into_data(ch),
+            ASLANArgChar::Separator => {}
+        }
         if ch == ']' {
             // Spec: Instruction delimiter of the form [<PREFIX>i_<CONTENT>:<ARG0>:<ARG1>:<ARG2>:...]
             // VALID INSTRUCTION DELIMITER
             let (index, part_index) = self.get_instruction_indices();
             self.state = ASLANParserState::Data;
+            let span = self.complete_delimiter();
+            self.record_delimiter_span(span);
             self.delimiter_buffer.clear();
             self.current_value.clear();
             
@@ -954,15 +2236,28 @@ impl ASLANParser {
             let is_keep_first = self.stack.last().unwrap().data_insertion_types.get(&current_key) == Some(&ASLANDataInsertionType::KeepFirst);
             
             if !already_seen || !is_keep_first {
-                let args = self.current_delimiter.as_ref().unwrap().args.clone();
+                let mut args = self.current_delimiter.as_ref().unwrap().args.clone();
                 let content = self.current_delimiter.as_ref().unwrap().content.clone().unwrap_or_default();
-                self.register_instruction(ASLANRegisteredInstruction {
-                    name: content,
-                    index,
-                    args,
-                    key: current_key.clone(),
-                    part_index,
-                });
+                let marker = args.first().filter(|_| self.parser_settings.ranged_instructions).cloned();
+                if marker.as_deref() == Some("end") {
+                    self.close_instruction_range(&content, index, part_index, span);
+                } else {
+                    let ranged = marker.as_deref() == Some("start");
+                    if ranged {
+                        args.remove(0);
+                    }
+                    let named_args = self.named_args(&args);
+                    self.register_instruction(ASLANRegisteredInstruction {
+                        name: content,
+                        index,
+                        args,
+                        key: current_key.clone(),
+                        part_index,
+                        named_args,
+                        ranged,
+                        end: None,
+                    });
+                }
                 
                 let is_object = self.get_value_at_key(&current_key).map(|v| v.is_object() || v.is_array()).unwrap_or(false);
                 if !is_object {
@@ -975,36 +2270,129 @@ impl ASLANParser {
             }
             return;
         }
-        if ch == ':' {
-            // Start a new arg
-            self.delimiter_buffer.push(ch);
-            self.current_delimiter.as_mut().unwrap().args.push(String::new());
-            return;
-        }
-        // Add to the current arg
-        let args = &mut self.current_delimiter.as_mut().unwrap().args;
-        if let Some(last) = args.last_mut() {
-            last.push(ch);
+        // Start a new arg
+        self.delimiter_buffer.push(ch);
+        self.current_delimiter.as_mut().unwrap().args.push(String::new());
+    }
+
+    /// Read a char of the current delimiter arg. Only `:` and `]` are separators, unless
+    /// `quoted_args` lets them appear inside quotes or after a `\`
+    fn read_arg_char(&mut self, ch: char) -> ASLANArgChar {
+        let quoted_args = self.parser_settings.quoted_args;
+        let delimiter = self.current_delimiter.as_mut().unwrap();
+        let Some(arg) = delimiter.args.last_mut() else {
+            return ASLANArgChar::Invalid;
+        };
+        let (quoting, literal) = match (delimiter.arg_quoting, ch) {
+            (ASLANArgQuoting::Plain | ASLANArgQuoting::AfterQuote, ':' | ']') => {
+                delimiter.arg_quoting = ASLANArgQuoting::Plain;
+                return ASLANArgChar::Separator;
+            }
+            (_, _) if !quoted_args => (ASLANArgQuoting::Plain, true),
+            (ASLANArgQuoting::Plain, '\\') => (ASLANArgQuoting::PlainEscape, false),
+            (ASLANArgQuoting::Plain, '"') if arg.is_empty() => (ASLANArgQuoting::Quoted, false),
+            (ASLANArgQuoting::Plain | ASLANArgQuoting::PlainEscape, _) => (ASLANArgQuoting::Plain, true),
+            (ASLANArgQuoting::Quoted, '\\') => (ASLANArgQuoting::QuotedEscape, false),
+            (ASLANArgQuoting::Quoted, '"') => (ASLANArgQuoting::AfterQuote, false),
+            (ASLANArgQuoting::Quoted | ASLANArgQuoting::QuotedEscape, _) => (ASLANArgQuoting::Quoted, true),
+            // Only a separator may follow the closing quote
+            (ASLANArgQuoting::AfterQuote, _) => return ASLANArgChar::Invalid,
+        };
+        if literal {
+            arg.push(ch);
         }
+        delimiter.arg_quoting = quoting;
         self.delimiter_buffer.push(ch);
+        ASLANArgChar::Consumed
     }
 
     fn get_instruction_indices(&self) -> (usize, usize) {
         let current_key = self.get_current_key_string();
         let value = self.get_value_at_key(&current_key);
+        let unit = self.parser_settings.instruction_index_unit;
         
         if let Some(arr) = value.and_then(|v| v.as_array()) {
-            let index = arr.last().and_then(|v| v.as_str()).map(|s| s.len()).unwrap_or(0);
+            let index = arr.last().and_then(|v| v.as_str()).map(|s| unit.length(s)).unwrap_or(0);
             let part_index = arr.len().saturating_sub(1);
             (index, part_index)
         } else if let Some(s) = value.and_then(|v| v.as_str()) {
-            (s.len(), 0)
+            (unit.length(s), 0)
         } else {
             (0, 0)
         }
     }
 
+    /// `name=value` args when `named_args` is enabled. Later args overwrite earlier ones
+    fn named_args(&self, args: &[String]) -> HashMap<String, String> {
+        if !self.parser_settings.named_args {
+            return HashMap::new();
+        }
+        args.iter()
+            .filter_map(|arg| arg.split_once('='))
+            .filter(|(name, _)| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn record_annotations(&mut self, named_args: HashMap<String, String>) {
+        if named_args.is_empty() {
+            return;
+        }
+        let path = self.get_schema_path();
+        let multi_aslan_index = self.multi_aslan_results.len().saturating_sub(1);
+        let existing = self
+            .annotations
+            .iter_mut()
+            .rev()
+            .find(|annotations| annotations.multi_aslan_index == multi_aslan_index && annotations.path == path);
+        match existing {
+            Some(annotations) => annotations.named_args.extend(named_args),
+            None => self.annotations.push(ASLANFieldAnnotations {
+                path,
+                multi_aslan_index,
+                named_args,
+            }),
+        }
+    }
+
+    fn get_annotations_at_current_key(&self) -> HashMap<String, String> {
+        let path = self.get_schema_path();
+        let multi_aslan_index = self.multi_aslan_results.len().saturating_sub(1);
+        self.annotations
+            .iter()
+            .rev()
+            .find(|annotations| annotations.multi_aslan_index == multi_aslan_index && annotations.path == path)
+            .map(|annotations| annotations.named_args.clone())
+            .unwrap_or_default()
+    }
+
+    /// Close the latest open range of the instruction `name` in the current part
+    fn close_instruction_range(&mut self, name: &str, index: usize, part_index: usize, span: ASLANSpan) {
+        let current_key = self.get_current_key_string();
+        let open = self.stack.last_mut().unwrap().registered_instructions.iter_mut().rev().find(|instruction| {
+            instruction.ranged
+                && instruction.end.is_none()
+                && instruction.key == current_key
+                && instruction.part_index == part_index
+                && instruction.name == name
+        });
+        match open {
+            Some(instruction) => instruction.end = Some(index),
+            None => {
+                let message = format!("`end` marker of `{}` has no open range in this part and is ignored", name);
+                self.report_diagnostic(ASLANDiagnosticCode::UnmatchedRangeEnd, span.start, &message);
+            }
+        }
+    }
+
     fn register_instruction(&mut self, instruction: ASLANRegisteredInstruction) {
+        let allowed = self.get_schema_for_current_key().and_then(|s| s.allowed_instructions.clone());
+        if let Some(allowed) = allowed {
+            if !allowed.contains(&instruction.name) {
+                let message = format!("instruction `{}` is not one of {:?}", instruction.name, allowed);
+                self.report_violation_at_current_key(ASLANViolationKind::DisallowedInstruction, &message);
+            }
+        }
         self.stack.last_mut().unwrap().registered_instructions.push(instruction);
     }
 
@@ -1017,16 +2405,19 @@ impl ASLANParser {
             return self.exit_delimiter_into_data(ch);
         }
         if ch == ']' {
+            self.close_blocks_by_indentation(self.delimiter_span().start);
             let is_array = self.get_latest_result().is_array();
             if is_array {
                 // Spec: Data delimiters can have no <CONTENT> or args if the current result is an array.
                 // VALID DATA DELIMITER
                 self.state = ASLANParserState::Data;
+                let span = self.complete_delimiter();
                 self.delimiter_buffer.clear();
                 self.current_value.clear();
                 self.emit_end_events_if_required();
                 self.emit_end_data_events_if_required();
                 self.next_key();
+                self.record_delimiter_span(span);
                 return;
             }
             // Spec: Data delimiters must contain <CONTENT> if the current result is not an array.
@@ -1040,6 +2431,13 @@ impl ASLANParser {
             self.current_value.clear();
             return;
         }
+        if ch.is_ascii_alphanumeric() && self.parser_settings.tolerance.missing_underscore {
+            self.delimiter_repairs.push("a missing underscore before the name");
+            self.state = ASLANParserState::DataDelimiterName;
+            self.current_delimiter.as_mut().unwrap().content = Some(String::new());
+            self.current_value.clear();
+            return self.handle_data_delimiter_name(ch);
+        }
         // Spec: Data delimiters must be valid of the form [<PREFIX>d_<CONTENT>] or [<PREFIX>d_<CONTENT>:<ARG0>:<ARG1>:<ARG2>:...]
         // INVALID DATA DELIMITER
         self.exit_delimiter_into_data(ch);
@@ -1060,6 +2458,7 @@ impl ASLANParser {
                 return self.exit_delimiter_into_data(ch);
             }
             // Spec: Data may have arguments.
+            self.close_blocks_by_indentation(self.delimiter_span().start);
             self.state = ASLANParserState::DataDelimiterArgs;
             self.current_delimiter.as_mut().unwrap().args = vec![String::new()];
             self.current_value.clear();
@@ -1083,11 +2482,14 @@ impl ASLANParser {
             // Spec: Data delimiter of the form [<PREFIX>d_<CONTENT>]
             // VALID DATA DELIMITER
             self.state = ASLANParserState::Data;
+            let span = self.complete_delimiter();
+            self.close_blocks_by_indentation(span.start);
             self.emit_end_events_if_required();
             self.emit_end_data_events_if_required();
             self.next_key();
             self.delimiter_buffer.clear();
             self.set_data_insertion_type(ASLANDataInsertionType::Default);
+            self.record_delimiter_span(span);
             
             let current_key = self.get_current_key_string();
             let already_seen = self.stack.last().unwrap().already_seen_duplicate_keys.get(&current_key).copied().unwrap_or(false);
@@ -1096,7 +2498,7 @@ impl ASLANParser {
             
             if already_seen && value_exists && !is_object {
                 self.current_value = self.parser_settings.append_separator.clone();
-                self.store_current_value();
+                self.store_current_value_ending_at(None);
             }
             self.current_value.clear();
             return;
@@ -1115,19 +2517,38 @@ impl ASLANParser {
             self.state = ASLANParserState::Locked;
             return;
         }
+        match self.read_arg_char(ch) {
+            ASLANArgChar::Consumed => return,
+            ASLANArgChar::Invalid => return self.exit_delimiter_into_data(ch),
+            ASLANArgChar::Separator => {}
+        }
         if ch == ']' {
             // Spec: Data delimiter of the form [<PREFIX>d_<CONTENT>:<ARG0>:<ARG1>:<ARG2>:...]
             // VALID DATA DELIMITER
             self.state = ASLANParserState::Data;
+            let span = self.complete_delimiter();
             self.delimiter_buffer.clear();
             
             let arg = self.current_delimiter.as_ref().and_then(|d| d.args.first()).cloned().unwrap_or_default();
-            match arg.as_str() {
-                "a" => self.set_data_insertion_type(ASLANDataInsertionType::Append),
-                "f" => self.set_data_insertion_type(ASLANDataInsertionType::KeepFirst),
-                "l" => self.set_data_insertion_type(ASLANDataInsertionType::KeepLast),
-                _ => self.set_data_insertion_type(ASLANDataInsertionType::Default),
+            let insertion_type = match arg.as_str() {
+                "a" => ASLANDataInsertionType::Append,
+                "f" => ASLANDataInsertionType::KeepFirst,
+                "l" => ASLANDataInsertionType::KeepLast,
+                _ => ASLANDataInsertionType::Default,
+            };
+            let current_key = self.get_current_key_string();
+            let first_insertion_type = self.stack.last().unwrap().data_insertion_types.get(&current_key).copied();
+            if insertion_type != ASLANDataInsertionType::Default
+                && first_insertion_type.map(|first| first != insertion_type).unwrap_or(false)
+            {
+                let message = format!("`:{}` is ignored as the first `{}` field set the duplicate behavior", arg, current_key);
+                self.report_diagnostic(ASLANDiagnosticCode::ConflictingDuplicateBehavior, span.start, &message);
             }
+            self.set_data_insertion_type(insertion_type);
+            self.record_delimiter_span(span);
+            let args = self.current_delimiter.as_ref().map(|d| d.args.clone()).unwrap_or_default();
+            let named_args = self.named_args(&args);
+            self.record_annotations(named_args);
             self.emit_end_events_if_required();
             self.emit_end_data_events_if_required();
             
@@ -1143,23 +2564,14 @@ impl ASLANParser {
                 && !is_object 
             {
                 self.current_value = self.parser_settings.append_separator.clone();
-                self.store_current_value();
+                self.store_current_value_ending_at(None);
             }
             self.current_value.clear();
             return;
         }
-        if ch == ':' {
-            // Start a new arg
-            self.delimiter_buffer.push(ch);
-            self.current_delimiter.as_mut().unwrap().args.push(String::new());
-            return;
-        }
-        // Add to the current arg
-        let args = &mut self.current_delimiter.as_mut().unwrap().args;
-        if let Some(last) = args.last_mut() {
-            last.push(ch);
-        }
+        // Start a new arg
         self.delimiter_buffer.push(ch);
+        self.current_delimiter.as_mut().unwrap().args.push(String::new());
     }
 
     fn handle_array_delimiter(&mut self, ch: char) {
@@ -1174,7 +2586,19 @@ impl ASLANParser {
             // Spec: Array delimiters have no <CONTENT> or args
             // VALID ARRAY DELIMITER
             self.state = ASLANParserState::Array;
+            let span = self.complete_delimiter();
             self.delimiter_buffer.clear();
+
+            // Let the schema decide when it describes the current key
+            if let Some(expects_array) = self.schema_expects_block_at_current_key(ASLANSchemaType::Array) {
+                if expects_array && self.can_open_block_at_current_key() {
+                    self.create_new_array(span);
+                } else {
+                    self.close_block(span);
+                }
+                return;
+            }
+
             let second_most_recent = self.get_2nd_most_recent_material_delimiter().copied();
             
             if self.get_object_safe_latest_result() || second_most_recent != Some(ASLANDelimiterType::Data) {
@@ -1185,21 +2609,16 @@ impl ASLANParser {
                     let already_seen = self.stack.last().unwrap().already_seen_duplicate_keys.get(&current_key).copied().unwrap_or(false);
                     if already_seen {
                         self.stack.last_mut().unwrap().already_seen_duplicate_keys.insert(current_key, false);
-                        self.create_new_array();
+                        self.create_new_array(span);
                         return;
                     }
-                    if self.stack.len() > 1 {
-                        self.emit_end_events_if_required();
-                        self.emit_end_data_events_if_required();
-                        self.sync_stack_to_root();
-                        self.stack.pop();
-                    }
+                    self.close_block(span);
                 } else {
-                    self.create_new_array();
+                    self.create_new_array(span);
                 }
                 return;
             }
-            self.create_new_array();
+            self.create_new_array(span);
             return;
         }
         // Spec: Array delimiters have no <CONTENT> or args
@@ -1207,9 +2626,13 @@ impl ASLANParser {
         self.exit_delimiter_into_data(ch);
     }
 
-    fn create_new_array(&mut self) {
+    fn create_new_array(&mut self, span: ASLANSpan) {
         self.current_value.clear();
         let current_key = self.get_current_key_string();
+        // Whatever was stored at this key before is replaced
+        self.clear_spans_under_current_key();
+        self.record_delimiter_span(span);
+        self.lex_block_edge(ASLANBlockEdge::Open);
         
         // Set the value to an empty array
         let latest = self.get_latest_result_mut();
@@ -1237,6 +2660,8 @@ impl ASLANParser {
             already_seen_duplicate_keys: HashMap::new(),
             implicit_arrays: HashMap::new(),
             registered_instructions: Vec::new(),
+            opening_indent: self.line_indent,
+            child_indent: None,
         });
     }
 
@@ -1252,10 +2677,22 @@ impl ASLANParser {
             // Spec: Void delimiters have no <CONTENT> or args
             // VALID VOID DELIMITER
             self.state = ASLANParserState::Data;
+            let span = self.complete_delimiter();
+            self.record_delimiter_span(span);
             self.delimiter_buffer.clear();
             self.current_value.clear();
+            if self.get_schema_for_current_key().map(|s| !s.nullable).unwrap_or(false) {
+                self.report_violation_at_current_key(
+                    ASLANViolationKind::UnexpectedNull,
+                    "value is void but the schema does not allow null",
+                );
+            }
             let current_key = self.get_current_key_string();
-            self.stack.last_mut().unwrap().void_fields.insert(current_key, true);
+            if self.stack.last_mut().unwrap().void_fields.insert(current_key, true).is_some() {
+                self.report_diagnostic(ASLANDiagnosticCode::DuplicateVoid, span.start, "field is already void");
+            }
+            // Spec: A void field is null. Store the null now, as the next char may already start another delimiter
+            self.store_current_value();
             return;
         }
         // Spec: Void delimiters have no <CONTENT> or args
@@ -1274,6 +2711,7 @@ impl ASLANParser {
         if ch == ']' {
             // Spec: Comment delimiters have no <CONTENT> or args
             // VALID COMMENT DELIMITER
+            self.complete_delimiter();
             self.state = ASLANParserState::Comment;
             self.delimiter_buffer.clear();
             self.current_value.clear();
@@ -1328,22 +2766,27 @@ impl ASLANParser {
             // Spec: Escape delimiter of the form [<PREFIX>e_<CONTENT>]
             // VALID ESCAPE DELIMITER
             self.state = ASLANParserState::Escape;
+            let span = self.delimiter_span();
             self.delimiter_buffer.clear();
             self.current_value.clear();
             
             if self.current_escape_delimiter.is_none() {
+                self.lex_delimiter(span);
                 self.current_escape_delimiter = Some(content);
+                self.record_delimiter_span(span);
             } else if self.current_escape_delimiter.as_ref() != Some(&content) {
                 // Make sure we write out the escape delimiter with different content since the escape hasn't closed
                 let prefix = self.current_delimiter.as_ref().unwrap().prefix.clone().unwrap_or_default();
                 let escape_content = self.current_delimiter.as_ref().unwrap().content.clone().unwrap_or_default();
                 self.current_value = format!("[{}e_{}", prefix, escape_content);
-                self.store_current_value();
+                self.store_current_value_ending_at(Some(span.end - ch.len_utf8()));
                 // Spec: Escape delimiters must be the same for the entire string.
                 // INVALID ESCAPE DELIMITER
                 return self.exit_delimiter_into_data(ch);
             } else {
+                self.lex_delimiter(span);
                 self.current_escape_delimiter = None;
+                self.record_delimiter_span(span);
                 self.state = ASLANParserState::Data;
                 self.delimiter_buffer.clear();
                 self.current_value.clear();
@@ -1370,6 +2813,13 @@ impl ASLANParser {
         if ch == ']' {
             // Spec: Part delimiters have no <CONTENT> or args
             // VALID PART DELIMITER
+            let span = self.complete_delimiter();
+            self.record_delimiter_span(span);
+            let scalar_type = self.get_schema_for_current_key().map(|s| s.schema_type).filter(|t| t.is_scalar());
+            if let Some(scalar_type) = scalar_type {
+                let message = format!("expected {} but found parts", scalar_type.name());
+                self.report_violation_at_current_key(ASLANViolationKind::TypeMismatch, &message);
+            }
             let current_key = self.get_current_key_string();
             let is_locked = self.stack.last().unwrap().data_insertion_locks.get(&current_key).copied().unwrap_or(false);
             
@@ -1490,6 +2940,9 @@ impl ASLANParser {
             self.delimiter_buffer.push(ch);
             return;
         }
+        if self.start_escaped_bracket(ch) {
+            return;
+        }
         self.append_to_current_value(ch);
         self.store_current_value();
     }
@@ -1499,9 +2952,18 @@ impl ASLANParser {
     }
 
     fn store_current_value(&mut self) {
+        self.store_current_value_ending_at(Some(self.input_offset));
+    }
+
+    /// Store the current value, which was read from the input right before `source_end`.
+    /// `None` means the value did not come from the input, e.g. an append separator.
+    fn store_current_value_ending_at(&mut self, source_end: Option<usize>) {
         let current_key = self.get_current_key_string();
         
         if self.stack.last().unwrap().void_fields.get(&current_key).copied().unwrap_or(false) {
+            if let Some(end) = source_end.filter(|_| !self.current_value.trim().is_empty()) {
+                self.report_content_after_void(end - self.current_value.len());
+            }
             self.current_value.clear();
             self.set_value_at_key(&current_key, Value::Null);
             return;
@@ -1512,6 +2974,12 @@ impl ASLANParser {
             let is_object = self.get_value_at_key(&current_key).map(|v| v.is_object() || v.is_array()).unwrap_or(false);
             let is_implicit_array = self.stack.last().unwrap().implicit_arrays.get(&current_key).copied().unwrap_or(false);
             
+            if !is_locked && (!is_object || is_implicit_array) {
+                if let Some(end) = source_end {
+                    self.record_content_span(ASLANSpan { start: end - self.current_value.len(), end });
+                }
+            }
+
             if !is_locked && !is_object {
                 // Append to string value
                 let current = self.get_value_at_key(&current_key).and_then(|v| v.as_str()).unwrap_or("").to_string();
@@ -1538,6 +3006,17 @@ impl ASLANParser {
         }
     }
 
+    fn report_content_after_void(&mut self, offset: usize) {
+        // Data is stored a char at a time, so only report the start of each run
+        let path = self.get_schema_path();
+        if let Some(last) = self.diagnostics.last() {
+            if last.code == ASLANDiagnosticCode::ContentAfterVoid && last.path == path {
+                return;
+            }
+        }
+        self.report_diagnostic(ASLANDiagnosticCode::ContentAfterVoid, offset, "text after a void delimiter is dropped");
+    }
+
     fn set_value_at_key(&mut self, key: &str, value: Value) {
         // Update in both the latest result and propagate up the stack
         let frame = self.stack.last_mut().unwrap();
@@ -1618,6 +3097,7 @@ impl ASLANParser {
                     }
                     // Clear instructions for this key
                     frame.registered_instructions.retain(|i| i.key != current_key);
+                    self.clear_spans_under_current_key();
                 }
                 Some(ASLANDataInsertionType::KeepFirst) => {
                     frame.data_insertion_locks.insert(current_key, true);
@@ -1640,9 +3120,12 @@ impl ASLANParser {
                     let min = self.get_min_array_index();
                     self.set_min_array_index(min.max(new_index + 1));
                 } else {
+                    let message = format!("`{}` is not an array index, using the next index", content);
                     let min = self.get_min_array_index();
                     self.set_current_key(ASLANKey::Index(min));
                     self.set_min_array_index(min + 1);
+                    let offset = self.delimiter_span().start;
+                    self.report_diagnostic(ASLANDiagnosticCode::InvalidArrayIndex, offset, &message);
                 }
             } else {
                 // Implicit index
@@ -1673,6 +3156,7 @@ impl ASLANParser {
                 self.set_current_key(ASLANKey::String(content));
             }
         }
+        self.check_current_key_against_schema();
     }
 
     fn get_current_path(&self) -> Vec<String> {
@@ -1716,13 +3200,16 @@ impl ASLANParser {
         
         if !is_object && !is_implicit_array {
             let value = self.get_value_at_key(&current_key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+            let unit = self.parser_settings.instruction_index_unit;
             let instructions: Vec<ASLANInstructionInfo> = self.stack.last().unwrap().registered_instructions
                 .iter()
                 .filter(|i| i.key == current_key)
                 .map(|i| ASLANInstructionInfo {
                     name: i.name.clone(),
                     args: i.args.clone(),
+                    named_args: i.named_args.clone(),
                     index: i.index,
+                    range: i.range(unit.length(&value)),
                 })
                 .collect();
             
@@ -1737,15 +3224,19 @@ impl ASLANParser {
             let arr = self.get_value_at_key(&current_key).and_then(|v| v.as_array()).cloned().unwrap_or_default();
             let mut instructions_by_part: HashMap<usize, Vec<ASLANInstructionInfo>> = HashMap::new();
             
+            let unit = self.parser_settings.instruction_index_unit;
             for instruction in &self.stack.last().unwrap().registered_instructions {
                 if instruction.key == current_key {
+                    let part_length = arr.get(instruction.part_index).and_then(|v| v.as_str()).map(|s| unit.length(s)).unwrap_or(0);
                     instructions_by_part
                         .entry(instruction.part_index)
                         .or_default()
                         .push(ASLANInstructionInfo {
                             name: instruction.name.clone(),
                             args: instruction.args.clone(),
+                            named_args: instruction.named_args.clone(),
                             index: instruction.index,
+                            range: instruction.range(part_length),
                         });
                 }
             }
@@ -1779,6 +3270,7 @@ impl ASLANParser {
         let path = self.get_current_path();
         let structure = self.get_result();
         let multi_aslan_index = self.multi_aslan_results.len().saturating_sub(1);
+        let spans = self.get_spans_at_current_key();
         
         let instructions: Vec<_> = self.stack.last().unwrap().registered_instructions
             .iter()
@@ -1786,8 +3278,10 @@ impl ASLANParser {
             .cloned()
             .collect();
         
+        let part_length = self.parser_settings.instruction_index_unit.length(&value);
         for instruction in instructions {
             let event = ASLANInstruction {
+                range: instruction.range(part_length),
                 content: value.clone(),
                 part_index: 0,
                 field_name: current_key.clone(),
@@ -1795,9 +3289,11 @@ impl ASLANParser {
                 structure: structure.clone(),
                 instruction: instruction.name,
                 args: instruction.args,
+                named_args: instruction.named_args,
                 index: instruction.index,
                 multi_aslan_index,
                 tag: tag.to_string(),
+                spans: spans.clone(),
             };
             
             if tag == "content" {
@@ -1831,6 +3327,7 @@ impl ASLANParser {
         let path = self.get_current_path();
         let structure = self.get_result();
         let multi_aslan_index = self.multi_aslan_results.len().saturating_sub(1);
+        let spans = self.get_spans_at_current_key();
         
         let instructions: Vec<_> = self.stack.last().unwrap().registered_instructions
             .iter()
@@ -1838,8 +3335,10 @@ impl ASLANParser {
             .cloned()
             .collect();
         
+        let part_length = self.parser_settings.instruction_index_unit.length(&value);
         for instruction in instructions {
             let event = ASLANInstruction {
+                range: instruction.range(part_length),
                 content: value.clone(),
                 part_index,
                 field_name: current_key.clone(),
@@ -1847,9 +3346,11 @@ impl ASLANParser {
                 structure: structure.clone(),
                 instruction: instruction.name,
                 args: instruction.args,
+                named_args: instruction.named_args,
                 index: instruction.index,
                 multi_aslan_index,
                 tag: tag.to_string(),
+                spans: spans.clone(),
             };
             
             if tag == "content" {
@@ -1880,6 +3381,9 @@ impl ASLANParser {
             structure,
             multi_aslan_index,
             tag: "end_data".to_string(),
+            spans: self.get_spans_at_current_key(),
+            annotations: self.get_annotations_at_current_key(),
+            index_unit: self.parser_settings.instruction_index_unit,
         };
         
         for (_, handler) in &mut self.event_listeners.end_data {
@@ -0,0 +1,90 @@
+use aslan::schema;
+use serde_json::Value;
+use std::io::Read;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: aslan infer [--text] [--default-field NAME] [FILE...]
+
+Infer an ASLAN schema from parse results. Each FILE (or stdin if none are given)
+holds a JSON array of results, a single result or one result per line.
+The schema is printed as JSON, or as a readable field list with --text.
+Pass --default-field when the results were parsed with a renamed default field.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(|a| a.as_str()) {
+        Some("infer") => match infer(&args[1..]) {
+            Ok(output) => {
+                print!("{}", output);
+                ExitCode::SUCCESS
+            }
+            Err(message) => {
+                eprintln!("aslan: {}", message);
+                ExitCode::FAILURE
+            }
+        },
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn infer(args: &[String]) -> Result<String, String> {
+    let mut text = false;
+    let mut default_field_name = "_default".to_string();
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--text" => text = true,
+            "--default-field" => {
+                default_field_name = args.next().ok_or("`--default-field` needs a field name")?.clone();
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option `{}`", flag)),
+            file => files.push(file.to_string()),
+        }
+    }
+
+    let mut values = Vec::new();
+    if files.is_empty() {
+        let mut input = String::new();
+        std::io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("cannot read stdin: {}", e))?;
+        values.extend(read_results(&input, "stdin")?);
+    }
+    for file in &files {
+        let input = std::fs::read_to_string(file).map_err(|e| format!("cannot read {}: {}", file, e))?;
+        values.extend(read_results(&input, file)?);
+    }
+
+    let schema = schema::infer_with_default_field(&values, &default_field_name);
+    if text {
+        Ok(schema.describe_format())
+    } else {
+        serde_json::to_string_pretty(&schema)
+            .map(|json| json + "\n")
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn read_results(input: &str, source: &str) -> Result<Vec<Value>, String> {
+    match serde_json::from_str::<Value>(input) {
+        Ok(Value::Array(values)) => Ok(values),
+        Ok(value) => Ok(vec![value]),
+        // JSON Lines
+        Err(_) => input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).map_err(|e| format!("{}:{}: invalid JSON: {}", source, i + 1, e))
+            })
+            .collect(),
+    }
+}
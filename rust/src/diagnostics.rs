@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How much a recovery may have changed the result compared to what the writer intended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ASLANDiagnosticSeverity {
+    /// The result is very likely what was intended
+    Info,
+    /// Some input was dropped or reinterpreted
+    Warning,
+    /// The input is probably truncated or broken
+    Error,
+}
+
+/// Kinds of malformed input the parser recovers from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ASLANDiagnosticCode {
+    /// A data delimiter in an array names something other than an index. The next index is used
+    InvalidArrayIndex,
+    /// A void delimiter in a field that is already void
+    DuplicateVoid,
+    /// Text after a void delimiter in the same field. It is dropped
+    ContentAfterVoid,
+    /// An escape is still open at the end of the input
+    UnterminatedEscape,
+    /// An object or array delimiter closes a block that was never opened. It is ignored
+    ExtraneousClose,
+    /// A delimiter with a reserved suffix. It is dropped
+    ReservedDelimiter,
+    /// A repeated field asks for a different duplicate behavior than its first occurrence. The first one wins
+    ConflictingDuplicateBehavior,
+    /// Text starting with the delimiter prefix that does not form a delimiter. It is kept as data
+    MalformedDelimiter,
+    /// An object or array is closed because a later field is indented out of it, see `repair_indentation`
+    InferredClose,
+    /// A near-miss delimiter accepted by one of the `ASLANTolerance` rules, e.g. `[ASLANd_title]`
+    RepairedDelimiter,
+    /// An `end` marker of a ranged instruction without an open range in the same part. It is ignored
+    UnmatchedRangeEnd,
+}
+
+impl ASLANDiagnosticCode {
+    pub fn severity(&self) -> ASLANDiagnosticSeverity {
+        match self {
+            ASLANDiagnosticCode::DuplicateVoid | ASLANDiagnosticCode::RepairedDelimiter => ASLANDiagnosticSeverity::Info,
+            ASLANDiagnosticCode::UnterminatedEscape => ASLANDiagnosticSeverity::Error,
+            _ => ASLANDiagnosticSeverity::Warning,
+        }
+    }
+}
+
+/// A recovery the parser made while reading malformed input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ASLANDiagnostic {
+    pub code: ASLANDiagnosticCode,
+    pub severity: ASLANDiagnosticSeverity,
+    pub message: String,
+    /// Byte offset into everything passed to the parser so far
+    pub offset: usize,
+    /// Path of the value in the result the recovery applies to
+    pub path: Vec<String>,
+}
+
+impl fmt::Display for ASLANDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:?}: {}", self.offset, self.severity, self.message)
+    }
+}
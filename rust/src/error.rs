@@ -0,0 +1,108 @@
+use crate::diagnostics::{ASLANDiagnostic, ASLANDiagnosticCode};
+use crate::schema::ASLANViolation;
+use std::fmt;
+
+/// Error returned by `try_parse` and `try_close` in strict mode. Each repair lenient parsing
+/// would make has its own variant carrying the diagnostic, see `ASLANDiagnosticCode`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ASLANError {
+    InvalidArrayIndex(ASLANDiagnostic),
+    DuplicateVoid(ASLANDiagnostic),
+    ContentAfterVoid(ASLANDiagnostic),
+    UnterminatedEscape(ASLANDiagnostic),
+    ExtraneousClose(ASLANDiagnostic),
+    ReservedDelimiter(ASLANDiagnostic),
+    ConflictingDuplicateBehavior(ASLANDiagnostic),
+    MalformedDelimiter(ASLANDiagnostic),
+    InferredClose(ASLANDiagnostic),
+    RepairedDelimiter(ASLANDiagnostic),
+    UnmatchedRangeEnd(ASLANDiagnostic),
+    /// The result does not match the schema
+    Violation(ASLANViolation),
+}
+
+impl ASLANError {
+    /// The repair the input needed, if it was malformed
+    pub fn diagnostic(&self) -> Option<&ASLANDiagnostic> {
+        match self {
+            ASLANError::InvalidArrayIndex(diagnostic)
+            | ASLANError::DuplicateVoid(diagnostic)
+            | ASLANError::ContentAfterVoid(diagnostic)
+            | ASLANError::UnterminatedEscape(diagnostic)
+            | ASLANError::ExtraneousClose(diagnostic)
+            | ASLANError::ReservedDelimiter(diagnostic)
+            | ASLANError::ConflictingDuplicateBehavior(diagnostic)
+            | ASLANError::MalformedDelimiter(diagnostic)
+            | ASLANError::InferredClose(diagnostic)
+            | ASLANError::RepairedDelimiter(diagnostic)
+            | ASLANError::UnmatchedRangeEnd(diagnostic) => Some(diagnostic),
+            ASLANError::Violation(_) => None,
+        }
+    }
+
+    /// The kind of repair the input needed, if it was malformed
+    pub fn code(&self) -> Option<ASLANDiagnosticCode> {
+        self.diagnostic().map(|diagnostic| diagnostic.code)
+    }
+}
+
+impl fmt::Display for ASLANError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self, self.diagnostic()) {
+            (ASLANError::Violation(violation), _) => write!(f, "schema violation at {}", violation),
+            (_, Some(diagnostic)) => write!(f, "malformed ASLAN at byte {}: {}", diagnostic.offset, diagnostic.message),
+            (_, None) => unreachable!("every other variant carries a diagnostic"),
+        }
+    }
+}
+
+impl std::error::Error for ASLANError {}
+
+impl From<ASLANDiagnostic> for ASLANError {
+    fn from(diagnostic: ASLANDiagnostic) -> Self {
+        match diagnostic.code {
+            ASLANDiagnosticCode::InvalidArrayIndex => ASLANError::InvalidArrayIndex(diagnostic),
+            ASLANDiagnosticCode::DuplicateVoid => ASLANError::DuplicateVoid(diagnostic),
+            ASLANDiagnosticCode::ContentAfterVoid => ASLANError::ContentAfterVoid(diagnostic),
+            ASLANDiagnosticCode::UnterminatedEscape => ASLANError::UnterminatedEscape(diagnostic),
+            ASLANDiagnosticCode::ExtraneousClose => ASLANError::ExtraneousClose(diagnostic),
+            ASLANDiagnosticCode::ReservedDelimiter => ASLANError::ReservedDelimiter(diagnostic),
+            ASLANDiagnosticCode::ConflictingDuplicateBehavior => ASLANError::ConflictingDuplicateBehavior(diagnostic),
+            ASLANDiagnosticCode::MalformedDelimiter => ASLANError::MalformedDelimiter(diagnostic),
+            ASLANDiagnosticCode::InferredClose => ASLANError::InferredClose(diagnostic),
+            ASLANDiagnosticCode::RepairedDelimiter => ASLANError::RepairedDelimiter(diagnostic),
+            ASLANDiagnosticCode::UnmatchedRangeEnd => ASLANError::UnmatchedRangeEnd(diagnostic),
+        }
+    }
+}
+
+impl From<ASLANViolation> for ASLANError {
+    fn from(violation: ASLANViolation) -> Self {
+        ASLANError::Violation(violation)
+    }
+}
+
+/// Error returned when parser settings are invalid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ASLANSettingsError {
+    /// Name of the offending option
+    pub field: String,
+    pub message: String,
+}
+
+impl ASLANSettingsError {
+    pub(crate) fn new(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ASLANSettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid setting `{}`: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ASLANSettingsError {}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Whether a custom delimiter takes `_<CONTENT>` after its suffix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ASLANExtensionContent {
+    #[default]
+    Forbidden,
+    Optional,
+    Required,
+}
+
+/// What may follow the suffix of a custom delimiter. Content follows the spec rules for
+/// delimiter content: alphanumeric characters and underscores, not starting or ending with one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ASLANExtensionGrammar {
+    pub content: ASLANExtensionContent,
+    /// Allow `:<ARG>` args after the suffix or content
+    pub args: bool,
+}
+
+/// A well-formed custom delimiter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ASLANExtensionDelimiter {
+    pub suffix: char,
+    pub content: Option<String>,
+    pub args: Vec<String>,
+}
+
+/// The field a custom delimiter appears in, passed to `ASLANDelimiterExtension::handle`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ASLANExtensionContext {
+    /// Name of the current field
+    pub field_name: String,
+    /// Path of the current field in the result
+    pub path: Vec<String>,
+    /// Value of the current field. A changed value is written back to the result
+    pub value: Option<Value>,
+    pub(crate) events: Vec<Value>,
+}
+
+impl ASLANExtensionContext {
+    /// Emit a custom event to the listeners added with `ASLANParser::add_extension_listener`
+    pub fn emit(&mut self, data: Value) {
+        self.events.push(data);
+    }
+}
+
+/// Custom event emitted by an extension handler
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ASLANExtensionEvent {
+    pub suffix: char,
+    pub field_name: String,
+    pub path: Vec<String>,
+    pub data: Value,
+}
+
+/// A custom delimiter suffix, e.g. `t` for typed values as in `[aslant_integer]`.
+///
+/// Spec §5 reserves every suffix it does not define, so only parsers the extension is registered
+/// with via `ASLANParser::register_extension` understand it. Other parsers drop the delimiter.
+pub trait ASLANDelimiterExtension {
+    /// The suffix character. Must be alphanumeric and not defined by the spec
+    fn suffix(&self) -> char;
+
+    /// What may follow the suffix. Defaults to nothing, as in `[aslant]`
+    fn grammar(&self) -> ASLANExtensionGrammar {
+        ASLANExtensionGrammar::default()
+    }
+
+    /// Called for each well-formed delimiter with this suffix
+    fn handle(&mut self, delimiter: &ASLANExtensionDelimiter, context: &mut ASLANExtensionContext);
+}
@@ -0,0 +1,429 @@
+use crate::aslan_parser::ASLANDuplicateKeyBehavior;
+use crate::schema::{ASLANSchema, ASLANSchemaType};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde_json::{Map, Value};
+
+const WORDS: &[&str] = &[
+    "alpha", "bravo", "river", "stone", "quiet", "orange", "signal", "maple", "vector", "harbor", "lantern", "copper",
+    "meadow", "pixel", "summit", "thread", "violet", "window", "yonder", "zephyr",
+];
+
+const PREAMBLES: &[&str] = &[
+    "Sure! Here is the result:\n",
+    "Here you go.\n\n",
+    "Certainly, I've filled in every field below.\n",
+    "Let me structure that for you: ",
+];
+
+const COMMENTS: &[&str] = &[
+    "double-checking this value",
+    "not sure about this one",
+    "moving on to the next field\n",
+    "TODO: revisit",
+];
+
+/// Text that looks like markup but is not a delimiter. `{p}` is replaced with the prefix.
+const DELIMITER_LIKE_NOISE: &[&str] = &["[Your name]", "[1]", " [see above] ", "[{p}x y]", "[{p}", "[]", "[ {p}d_no ]"];
+
+/// Noise the generator adds around the data
+#[derive(Debug, Clone)]
+pub struct ASLANGeneratorSettings {
+    pub prefix: String,
+    /// Text before the first field, which ends up in `_default`
+    pub preamble: bool,
+    /// Comments after values
+    pub comments: bool,
+    /// Whitespace between delimiters that the parser ignores
+    pub whitespace: bool,
+    /// Repeated fields using the append, first and last duplicate behaviors
+    pub duplicate_keys: bool,
+    /// Voids for nullable fields, sometimes repeated
+    pub voids: bool,
+    /// Bracketed text inside values that is not a delimiter
+    pub delimiter_like_noise: bool,
+    /// Upper bound for the length in chars of each chunk
+    pub max_chunk_len: usize,
+}
+
+impl Default for ASLANGeneratorSettings {
+    fn default() -> Self {
+        Self {
+            prefix: "aslan".to_string(),
+            preamble: true,
+            comments: true,
+            whitespace: true,
+            duplicate_keys: true,
+            voids: true,
+            delimiter_like_noise: true,
+            max_chunk_len: 16,
+        }
+    }
+}
+
+/// A generated ASLAN document and the result `ASLANParser` should produce for it
+/// with default settings and the same prefix
+#[derive(Debug, Clone, PartialEq)]
+pub struct ASLANGeneratedDocument {
+    pub text: String,
+    /// `text` split at random char boundaries, as an LLM stream would deliver it
+    pub chunks: Vec<String>,
+    pub expected: Value,
+}
+
+/// The data to write, before noise is added
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Void,
+    Parts(Vec<String>, Vec<String>),
+    Object(Vec<(String, Option<ASLANDuplicateKeyBehavior>, Node)>),
+    Array(Vec<Node>),
+}
+
+/// Seeded generator of realistic, noisy ASLAN documents for property tests
+pub struct ASLANGenerator {
+    rng: StdRng,
+    settings: ASLANGeneratorSettings,
+}
+
+impl ASLANGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self::with_settings(seed, ASLANGeneratorSettings::default())
+    }
+
+    pub fn with_settings(seed: u64, settings: ASLANGeneratorSettings) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            settings,
+        }
+    }
+
+    /// Generate a document with random data of the shape described by `schema`.
+    /// A schema whose root is not an object is generated as the field `value`.
+    pub fn generate(&mut self, schema: &ASLANSchema) -> ASLANGeneratedDocument {
+        let root = if schema.schema_type == ASLANSchemaType::Object {
+            self.sample_schema(schema, "value", false)
+        } else {
+            let value = self.sample_schema(schema, "value", false);
+            Node::Object(vec![("value".to_string(), None, value)])
+        };
+        self.write_document(root)
+    }
+
+    /// Generate a document holding the given sample data.
+    /// Leaves are written as strings and empty strings as voids, as ASLAN has no empty fields.
+    /// A `_default` key is ignored and a non-object sample is generated as the field `value`.
+    pub fn generate_from_value(&mut self, value: &Value) -> ASLANGeneratedDocument {
+        let root = match value {
+            Value::Object(map) => Node::Object(
+                map.iter()
+                    .filter(|(key, _)| key.as_str() != "_default")
+                    .map(|(key, value)| (key.clone(), None, Self::sample_value(value)))
+                    .collect(),
+            ),
+            other => Node::Object(vec![("value".to_string(), None, Self::sample_value(other))]),
+        };
+        self.write_document(root)
+    }
+
+    fn sample_value(value: &Value) -> Node {
+        match value {
+            Value::Null => Node::Void,
+            Value::String(s) if s.is_empty() => Node::Void,
+            Value::String(s) => Node::Text(s.clone()),
+            Value::Bool(_) | Value::Number(_) => Node::Text(value.to_string()),
+            Value::Array(items) => Node::Array(items.iter().map(Self::sample_value).collect()),
+            Value::Object(map) => Node::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), None, Self::sample_value(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn sample_schema(&mut self, schema: &ASLANSchema, name: &str, in_array: bool) -> Node {
+        if schema.nullable && self.settings.voids && self.rng.gen_bool(0.2) {
+            return Node::Void;
+        }
+        match schema.schema_type {
+            ASLANSchemaType::Object => {
+                let mut fields = Vec::new();
+                for field in &schema.fields {
+                    if !field.required && self.rng.gen_bool(0.3) {
+                        continue;
+                    }
+                    let value = self.sample_schema(&field.schema, &field.name, false);
+                    fields.push((field.name.clone(), field.duplicate_behavior, value));
+                }
+                if schema.additional_fields && self.rng.gen_bool(0.2) {
+                    let value = Node::Text(self.sample_text("extra", false));
+                    fields.push((format!("extra_{}", self.rng.gen_range(1..100)), None, value));
+                }
+                Node::Object(fields)
+            }
+            ASLANSchemaType::Array => {
+                let min = schema.min_items.unwrap_or(0).max(1);
+                let max = schema.max_items.unwrap_or(min + 3).max(min);
+                let count = self.rng.gen_range(min..=max);
+                let items = schema.items.as_deref().cloned().unwrap_or_default();
+                Node::Array(
+                    (0..count)
+                        .map(|i| self.sample_schema(&items, &format!("{} {}", name, i + 1), true))
+                        .collect(),
+                )
+            }
+            // Parts are only written directly in fields, as the parser does not split array items
+            ASLANSchemaType::Parts if !in_array => {
+                let count = self.rng.gen_range(1..=3);
+                let parts = (0..count).map(|_| self.sample_text(name, false)).collect();
+                Node::Parts(parts, schema.allowed_instructions.clone().unwrap_or_default())
+            }
+            _ => {
+                let text = match (&schema.allowed_values, schema.schema_type) {
+                    (Some(values), _) if !values.is_empty() => values.choose(&mut self.rng).unwrap().clone(),
+                    (_, ASLANSchemaType::Integer) => self.rng.gen_range(-1000..1000).to_string(),
+                    (_, ASLANSchemaType::Number) => format!("{:.2}", self.rng.gen_range(-1000.0..1000.0)),
+                    (_, ASLANSchemaType::Boolean) => self.rng.gen_bool(0.5).to_string(),
+                    _ => self.sample_text(name, schema.escaped),
+                };
+                Node::Text(text)
+            }
+        }
+    }
+
+    fn sample_text(&mut self, name: &str, delimiter_in_text: bool) -> String {
+        let count = self.rng.gen_range(1..=6);
+        let mut words: Vec<String> = (0..count)
+            .map(|_| WORDS.choose(&mut self.rng).unwrap().to_string())
+            .collect();
+        words.insert(0, name.to_string());
+        if self.settings.delimiter_like_noise && self.rng.gen_bool(0.3) {
+            let noise = DELIMITER_LIKE_NOISE.choose(&mut self.rng).unwrap();
+            // Never last, as a partial delimiter at the end of the stream is dropped on close
+            let at = self.rng.gen_range(0..words.len());
+            words.insert(at, noise.replace("{p}", &self.settings.prefix));
+        }
+        if delimiter_in_text {
+            // Content that needs an escape, such as code that emits ASLAN
+            words.push(format!("[{}d_{}]", self.settings.prefix, name.replace(' ', "_")));
+        }
+        let mut text = words.join(" ");
+        if self.rng.gen_bool(0.3) {
+            text.push('\n');
+        }
+        text
+    }
+
+    fn write_document(&mut self, root: Node) -> ASLANGeneratedDocument {
+        let mut text = String::new();
+        let mut default = Value::Null;
+        if self.settings.preamble && self.rng.gen_bool(0.5) {
+            let preamble = PREAMBLES.choose(&mut self.rng).unwrap().to_string();
+            text.push_str(&preamble);
+            default = Value::String(preamble);
+        }
+        let mut expected = Map::new();
+        if let Node::Object(fields) = root {
+            if fields.is_empty() && default.is_null() {
+                default = Value::String(String::new());
+            }
+            self.write_fields(fields, false, &mut text, &mut expected);
+        }
+        expected.insert("_default".to_string(), default);
+
+        let chars: Vec<char> = text.chars().collect();
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let len = self.rng.gen_range(1..=self.settings.max_chunk_len.max(1));
+            let end = (start + len).min(chars.len());
+            chunks.push(chars[start..end].iter().collect());
+            start = end;
+        }
+
+        ASLANGeneratedDocument {
+            text,
+            chunks,
+            expected: Value::Object(expected),
+        }
+    }
+
+    /// Write the fields of an object. In a nested object, repeated fields are written before the
+    /// last field, as an object delimiter right after a repeated field opens a new object for it.
+    fn write_fields(
+        &mut self,
+        fields: Vec<(String, Option<ASLANDuplicateKeyBehavior>, Node)>,
+        nested: bool,
+        out: &mut String,
+        expected: &mut Map<String, Value>,
+    ) {
+        let prefix = self.settings.prefix.clone();
+        let count = fields.len();
+        let mut repeats = Vec::new();
+        for (i, (name, behavior, node)) in fields.into_iter().enumerate() {
+            let is_last = i + 1 == count;
+            if nested && is_last {
+                self.write_repeats(std::mem::take(&mut repeats), out);
+            }
+            let duplicate = match &node {
+                Node::Text(text) if self.settings.duplicate_keys && !(nested && is_last) && self.rng.gen_bool(0.2) => {
+                    let behavior = behavior.unwrap_or_else(|| {
+                        *[
+                            ASLANDuplicateKeyBehavior::Append,
+                            ASLANDuplicateKeyBehavior::KeepFirst,
+                            ASLANDuplicateKeyBehavior::KeepLast,
+                        ]
+                        .choose(&mut self.rng)
+                        .unwrap()
+                    });
+                    Some((behavior, text.clone()))
+                }
+                _ => None,
+            };
+            let args = match duplicate.as_ref().map(|(b, _)| *b).or(behavior) {
+                Some(ASLANDuplicateKeyBehavior::Append) => ":a",
+                Some(ASLANDuplicateKeyBehavior::KeepFirst) => ":f",
+                Some(ASLANDuplicateKeyBehavior::KeepLast) => ":l",
+                None => "",
+            };
+            out.push_str(&format!("[{}d_{}{}]", prefix, name, args));
+            match duplicate {
+                Some((behavior, text)) => {
+                    let (first, second) = match behavior {
+                        ASLANDuplicateKeyBehavior::Append => {
+                            let chars: Vec<char> = text.chars().collect();
+                            if chars.len() < 2 {
+                                (text.clone(), String::new())
+                            } else {
+                                let at = self.rng.gen_range(1..chars.len());
+                                (chars[..at].iter().collect(), chars[at..].iter().collect())
+                            }
+                        }
+                        ASLANDuplicateKeyBehavior::KeepFirst => (text.clone(), self.sample_text("ignored", false)),
+                        ASLANDuplicateKeyBehavior::KeepLast => (self.sample_text("replaced", false), text.clone()),
+                    };
+                    self.write_text(&first, out);
+                    if !second.is_empty() {
+                        repeats.push((name.clone(), second));
+                    }
+                    expected.insert(name, Value::String(text));
+                }
+                None => {
+                    let value = self.write_node(node, out);
+                    expected.insert(name, value);
+                }
+            }
+            self.maybe_comment(out);
+        }
+        self.write_repeats(repeats, out);
+    }
+
+    /// Repeat fields later in the same block scope
+    fn write_repeats(&mut self, repeats: Vec<(String, String)>, out: &mut String) {
+        let prefix = self.settings.prefix.clone();
+        for (name, text) in repeats {
+            out.push_str(&format!("[{}d_{}]", prefix, name));
+            self.write_text(&text, out);
+            self.maybe_comment(out);
+        }
+    }
+
+    fn write_node(&mut self, node: Node, out: &mut String) -> Value {
+        let prefix = self.settings.prefix.clone();
+        match node {
+            Node::Text(text) => {
+                self.write_text(&text, out);
+                Value::String(text)
+            }
+            Node::Void => {
+                out.push_str(&format!("[{}v]", prefix));
+                if self.settings.voids && self.rng.gen_bool(0.2) {
+                    out.push_str(&format!("[{}v]", prefix));
+                }
+                self.maybe_whitespace(out);
+                Value::Null
+            }
+            Node::Parts(parts, instructions) => {
+                let mut split = false;
+                for (i, part) in parts.iter().enumerate() {
+                    if i > 0 || self.rng.gen_bool(0.5) {
+                        split = true;
+                        out.push_str(&format!("[{}p]", prefix));
+                        if let Some(instruction) = instructions.choose(&mut self.rng) {
+                            if self.rng.gen_bool(0.5) {
+                                out.push_str(&format!("[{}i_{}]", prefix, instruction));
+                            }
+                        }
+                    }
+                    out.push_str(part);
+                }
+                if split {
+                    Value::Array(parts.into_iter().map(Value::String).collect())
+                } else {
+                    Value::String(parts.concat())
+                }
+            }
+            Node::Object(fields) => {
+                out.push_str(&format!("[{}o]", prefix));
+                self.maybe_whitespace(out);
+                let mut expected = Map::new();
+                self.write_fields(fields, true, out, &mut expected);
+                out.push_str(&format!("[{}o]", prefix));
+                self.maybe_whitespace(out);
+                Value::Object(expected)
+            }
+            Node::Array(items) => {
+                out.push_str(&format!("[{}a]", prefix));
+                self.maybe_whitespace(out);
+                let mut expected = Vec::new();
+                for item in items {
+                    out.push_str(&format!("[{}d]", prefix));
+                    expected.push(self.write_node(item, out));
+                    self.maybe_comment(out);
+                }
+                out.push_str(&format!("[{}a]", prefix));
+                self.maybe_whitespace(out);
+                Value::Array(expected)
+            }
+        }
+    }
+
+    /// Write text as a value, escaping it if it contains something the parser would read as a delimiter
+    fn write_text(&mut self, text: &str, out: &mut String) {
+        let prefix = &self.settings.prefix;
+        let start = format!("[{}", prefix);
+        let needs_escape = text.match_indices(&start).any(|(i, _)| {
+            matches!(
+                text[i + start.len()..].chars().next(),
+                Some('d' | 'o' | 'i' | 'a' | 'c' | 'e' | 'p' | 'v' | 'g' | 's')
+            )
+        });
+        if !needs_escape {
+            out.push_str(text);
+            return;
+        }
+        let mut tag = "RAW".to_string();
+        let mut n = 1;
+        while text.contains(&format!("[{}e_{}]", prefix, tag)) {
+            n += 1;
+            tag = format!("RAW{}", n);
+        }
+        out.push_str(&format!("[{}e_{}]{}[{}e_{}]", prefix, tag, text, prefix, tag));
+    }
+
+    fn maybe_comment(&mut self, out: &mut String) {
+        if self.settings.comments && self.rng.gen_bool(0.15) {
+            let comment = COMMENTS.choose(&mut self.rng).unwrap();
+            out.push_str(&format!("[{}c]{}", self.settings.prefix, comment));
+        }
+    }
+
+    fn maybe_whitespace(&mut self, out: &mut String) {
+        if self.settings.whitespace && self.rng.gen_bool(0.5) {
+            let whitespace = [" ", "\n", "\n\n", "  \n", "\t"].choose(&mut self.rng).unwrap();
+            out.push_str(whitespace);
+        }
+    }
+}
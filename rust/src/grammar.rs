@@ -0,0 +1,411 @@
+use crate::aslan_parser::ASLANDuplicateKeyBehavior;
+use crate::schema::{ASLANSchema, ASLANSchemaType};
+use std::collections::HashSet;
+
+/// Tag used to escape fields marked as `escaped`, matching `ASLANSchema::to_example`
+const ESCAPE_TAG: &str = "RAW";
+
+/// A grammar expression, rendered either as GBNF or as a regular expression
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(String),
+    /// A character class. Ranges such as `a-z` are written as-is, other characters are escaped.
+    Class {
+        negated: bool,
+        chars: Vec<char>,
+        ranges: &'static str,
+    },
+    Seq(Vec<Expr>),
+    Alt(Vec<Expr>),
+    Repeat(Box<Expr>, usize, Option<usize>),
+    Rule(String),
+}
+
+impl Expr {
+    fn class(chars: &[char], ranges: &'static str) -> Self {
+        Expr::Class {
+            negated: false,
+            chars: chars.to_vec(),
+            ranges,
+        }
+    }
+
+    fn not_class(chars: &[char]) -> Self {
+        Expr::Class {
+            negated: true,
+            chars: chars.to_vec(),
+            ranges: "",
+        }
+    }
+
+    fn lit(s: impl Into<String>) -> Self {
+        Expr::Literal(s.into())
+    }
+
+    fn rule(name: &str) -> Self {
+        Expr::Rule(name.to_string())
+    }
+
+    fn optional(self) -> Self {
+        Expr::Repeat(Box::new(self), 0, Some(1))
+    }
+
+    fn star(self) -> Self {
+        Expr::Repeat(Box::new(self), 0, None)
+    }
+
+    fn plus(self) -> Self {
+        Expr::Repeat(Box::new(self), 1, None)
+    }
+}
+
+/// Named rules built from a schema. `rules[0]` is the root.
+struct Grammar {
+    prefix: String,
+    rules: Vec<(String, Expr)>,
+    names: HashSet<String>,
+}
+
+impl Grammar {
+    fn build(schema: &ASLANSchema, prefix: &str) -> Self {
+        let mut grammar = Self {
+            prefix: prefix.to_string(),
+            rules: vec![("root".to_string(), Expr::Seq(Vec::new()))],
+            names: ["root", "ws", "text", "chunk", "escaped-text", "key", "instruction"]
+                .iter()
+                .map(|n| n.to_string())
+                .collect(),
+        };
+        let root = if schema.schema_type == ASLANSchemaType::Object {
+            grammar.fields(schema, "")
+        } else {
+            grammar.value(schema, "value")
+        };
+        grammar.rules[0].1 = root;
+
+        // Shared rules
+        let delimiter_start = format!("[{}", prefix);
+        grammar.push("ws", Expr::class(&[' ', '\t', '\r', '\n'], "").star());
+        grammar.push("chunk", not_containing_chunk(&delimiter_start));
+        grammar.push("text", Expr::rule("chunk").plus());
+        if grammar.uses("escaped-text") {
+            let escape = format!("[{}e_{}]", prefix, ESCAPE_TAG);
+            grammar.push("escaped-text", not_containing_chunk(&escape).plus());
+        }
+        if grammar.uses("key") {
+            grammar.push("key", Expr::class(&['_'], "a-zA-Z0-9").plus());
+        }
+        if grammar.uses("instruction") {
+            grammar.push(
+                "instruction",
+                Expr::Seq(vec![
+                    Expr::lit(format!("[{}i_", prefix)),
+                    Expr::class(&['_'], "a-zA-Z0-9").plus(),
+                    Expr::lit("]"),
+                ]),
+            );
+        }
+        grammar
+    }
+
+    fn push(&mut self, name: &str, expr: Expr) {
+        self.rules.push((name.to_string(), expr));
+    }
+
+    fn uses(&self, name: &str) -> bool {
+        fn refers(expr: &Expr, name: &str) -> bool {
+            match expr {
+                Expr::Rule(r) => r == name,
+                Expr::Seq(items) | Expr::Alt(items) => items.iter().any(|e| refers(e, name)),
+                Expr::Repeat(inner, _, _) => refers(inner, name),
+                _ => false,
+            }
+        }
+        self.rules.iter().any(|(_, expr)| refers(expr, name))
+    }
+
+    /// Reserve a unique GBNF rule name derived from a schema path
+    fn rule_name(&mut self, path: &str) -> String {
+        let mut base: String = path
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+            .collect();
+        base = base.trim_matches('-').to_string();
+        if base.is_empty() {
+            base = "field".to_string();
+        }
+        let mut name = base.clone();
+        let mut n = 2;
+        while self.names.contains(&name) {
+            name = format!("{}-{}", base, n);
+            n += 1;
+        }
+        self.names.insert(name.clone());
+        name
+    }
+
+    fn fields(&mut self, schema: &ASLANSchema, path: &str) -> Expr {
+        let mut seq = Vec::new();
+        for field in &schema.fields {
+            let field_path = if path.is_empty() {
+                field.name.clone()
+            } else {
+                format!("{}-{}", path, field.name)
+            };
+            let args = match field.duplicate_behavior {
+                Some(ASLANDuplicateKeyBehavior::Append) => ":a",
+                Some(ASLANDuplicateKeyBehavior::KeepFirst) => ":f",
+                Some(ASLANDuplicateKeyBehavior::KeepLast) => ":l",
+                None => "",
+            };
+            let value = self.value(&field.schema, &field_path);
+            let expr = Expr::Seq(vec![
+                Expr::lit(format!("[{}d_{}{}]", self.prefix, field.name, args)),
+                value,
+            ]);
+            seq.push(if field.required { expr } else { expr.optional() });
+        }
+        if schema.additional_fields {
+            seq.push(
+                Expr::Seq(vec![
+                    Expr::lit(format!("[{}d_", self.prefix)),
+                    Expr::rule("key"),
+                    Expr::lit("]"),
+                    Expr::rule("text"),
+                ])
+                .star(),
+            );
+        }
+        Expr::Seq(seq)
+    }
+
+    /// Add a rule for the value at `path` and return a reference to it
+    fn value(&mut self, schema: &ASLANSchema, path: &str) -> Expr {
+        let name = self.rule_name(path);
+        let index = self.rules.len();
+        self.rules.push((name.clone(), Expr::Seq(Vec::new())));
+
+        let p = self.prefix.clone();
+        let ws = Expr::rule("ws");
+        let digits = Expr::class(&[], "0-9").plus();
+        let mut expr = match schema.schema_type {
+            _ if schema.allowed_values.is_some() => Expr::Seq(vec![
+                ws.clone(),
+                Expr::Alt(schema.allowed_values.iter().flatten().map(|v| Expr::lit(v.clone())).collect()),
+                ws.clone(),
+            ]),
+            ASLANSchemaType::String if schema.escaped => Expr::Seq(vec![
+                Expr::lit(format!("[{}e_{}]", p, ESCAPE_TAG)),
+                Expr::rule("escaped-text"),
+                Expr::lit(format!("[{}e_{}]", p, ESCAPE_TAG)),
+                ws.clone(),
+            ]),
+            ASLANSchemaType::String => Expr::rule("text"),
+            ASLANSchemaType::Integer => Expr::Seq(vec![ws.clone(), Expr::lit("-").optional(), digits, ws.clone()]),
+            ASLANSchemaType::Number => Expr::Seq(vec![
+                ws.clone(),
+                Expr::lit("-").optional(),
+                digits.clone(),
+                Expr::Seq(vec![Expr::lit("."), digits.clone()]).optional(),
+                Expr::Seq(vec![Expr::class(&['e', 'E'], ""), Expr::class(&['-', '+'], "").optional(), digits]).optional(),
+                ws.clone(),
+            ]),
+            ASLANSchemaType::Boolean => Expr::Seq(vec![
+                ws.clone(),
+                Expr::Alt(vec![Expr::lit("true"), Expr::lit("false")]),
+                ws.clone(),
+            ]),
+            ASLANSchemaType::Parts => {
+                let instruction = match &schema.allowed_instructions {
+                    Some(allowed) if allowed.is_empty() => None,
+                    Some(allowed) => Some(Expr::Alt(
+                        allowed.iter().map(|i| Expr::lit(format!("[{}i_{}]", p, i))).collect(),
+                    )),
+                    None => Some(Expr::rule("instruction")),
+                };
+                let mut part = vec![Expr::lit(format!("[{}p]", p))];
+                if let Some(instruction) = instruction {
+                    part.push(instruction.star());
+                }
+                part.push(Expr::rule("text").optional());
+                Expr::Alt(vec![
+                    Expr::Seq(vec![Expr::rule("text").optional(), Expr::Seq(part).plus()]),
+                    Expr::rule("text"),
+                ])
+            }
+            ASLANSchemaType::Object => Expr::Seq(vec![
+                Expr::lit(format!("[{}o]", p)),
+                ws.clone(),
+                self.fields(schema, &name),
+                Expr::lit(format!("[{}o]", p)),
+                ws.clone(),
+            ]),
+            ASLANSchemaType::Array => {
+                let items = schema.items.as_deref().cloned().unwrap_or_default();
+                let item = Expr::Seq(vec![
+                    Expr::lit(format!("[{}d]", p)),
+                    self.value(&items, &format!("{}-item", name)),
+                ]);
+                Expr::Seq(vec![
+                    Expr::lit(format!("[{}a]", p)),
+                    ws.clone(),
+                    Expr::Repeat(Box::new(item), schema.min_items.unwrap_or(0), schema.max_items),
+                    Expr::lit(format!("[{}a]", p)),
+                    ws.clone(),
+                ])
+            }
+        };
+        if schema.nullable {
+            expr = Expr::Alt(vec![expr, Expr::Seq(vec![Expr::lit(format!("[{}v]", p)), ws])]);
+        }
+        self.rules[index].1 = expr;
+        Expr::Rule(name)
+    }
+
+    fn get(&self, name: &str) -> &Expr {
+        &self.rules.iter().find(|(n, _)| n == name).expect("rule is defined").1
+    }
+}
+
+/// One character of text that never completes `literal`, or a proper prefix of `literal`
+/// followed by a character that breaks it. `literal` must start with `[` and contain no other `[`.
+fn not_containing_chunk(literal: &str) -> Expr {
+    let chars: Vec<char> = literal.chars().collect();
+    let mut tail = Expr::not_class(&[chars[chars.len() - 1], '[']);
+    for &c in chars[1..chars.len() - 1].iter().rev() {
+        tail = Expr::Alt(vec![Expr::not_class(&[c, '[']), Expr::Seq(vec![Expr::lit(c.to_string()), tail])]);
+    }
+    Expr::Alt(vec![Expr::not_class(&['[']), Expr::Seq(vec![Expr::lit("["), tail])])
+}
+
+/// Render a character class. GBNF has no `\^` or `\-` escapes, so there `-` is written last
+/// where it cannot form a range.
+fn render_class(negated: bool, chars: &[char], ranges: &str, gbnf: bool) -> String {
+    let mut out = String::from("[");
+    if negated {
+        out.push('^');
+    }
+    out.push_str(ranges);
+    let mut chars = chars.to_vec();
+    if gbnf {
+        chars.sort_by_key(|&c| c == '-');
+    }
+    for c in chars {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\\' | ']' | '[' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '^' | '-' if !gbnf => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out.push(']');
+    out
+}
+
+fn render_repeat(min: usize, max: Option<usize>) -> String {
+    match (min, max) {
+        (0, None) => "*".to_string(),
+        (1, None) => "+".to_string(),
+        (0, Some(1)) => "?".to_string(),
+        (min, None) => format!("{{{},}}", min),
+        (min, Some(max)) if min == max => format!("{{{}}}", min),
+        (min, Some(max)) => format!("{{{},{}}}", min, max),
+    }
+}
+
+fn render_gbnf(expr: &Expr) -> String {
+    match expr {
+        Expr::Literal(s) => {
+            let mut out = String::from("\"");
+            for c in s.chars() {
+                match c {
+                    '"' => out.push_str("\\\""),
+                    '\\' => out.push_str("\\\\"),
+                    '\n' => out.push_str("\\n"),
+                    '\t' => out.push_str("\\t"),
+                    '\r' => out.push_str("\\r"),
+                    _ => out.push(c),
+                }
+            }
+            out.push('"');
+            out
+        }
+        Expr::Class { negated, chars, ranges } => render_class(*negated, chars, ranges, true),
+        Expr::Seq(items) if items.is_empty() => "\"\"".to_string(),
+        Expr::Seq(items) => items
+            .iter()
+            .map(|item| match item {
+                Expr::Alt(_) => format!("({})", render_gbnf(item)),
+                _ => render_gbnf(item),
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+        Expr::Alt(items) => items.iter().map(render_gbnf).collect::<Vec<_>>().join(" | "),
+        Expr::Repeat(inner, min, max) => {
+            let inner = match inner.as_ref() {
+                Expr::Literal(_) | Expr::Class { .. } | Expr::Rule(_) => render_gbnf(inner),
+                _ => format!("({})", render_gbnf(inner)),
+            };
+            format!("{}{}", inner, render_repeat(*min, *max))
+        }
+        Expr::Rule(name) => name.clone(),
+    }
+}
+
+fn render_regex(expr: &Expr, grammar: &Grammar) -> String {
+    match expr {
+        Expr::Literal(s) => {
+            let mut out = String::new();
+            for c in s.chars() {
+                match c {
+                    '\\' | '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$' => {
+                        out.push('\\');
+                        out.push(c);
+                    }
+                    '\n' => out.push_str("\\n"),
+                    '\t' => out.push_str("\\t"),
+                    '\r' => out.push_str("\\r"),
+                    _ => out.push(c),
+                }
+            }
+            out
+        }
+        Expr::Class { negated, chars, ranges } => render_class(*negated, chars, ranges, false),
+        Expr::Seq(items) => items.iter().map(|item| render_regex(item, grammar)).collect(),
+        Expr::Alt(items) => format!(
+            "(?:{})",
+            items.iter().map(|item| render_regex(item, grammar)).collect::<Vec<_>>().join("|")
+        ),
+        Expr::Repeat(inner, min, max) => format!("(?:{}){}", render_regex(inner, grammar), render_repeat(*min, *max)),
+        Expr::Rule(name) => format!("(?:{})", render_regex(grammar.get(name), grammar)),
+    }
+}
+
+/// Export a GBNF grammar (as used by llama.cpp) that only admits ASLAN documents with the
+/// shape of `schema`, using delimiters with the given prefix.
+///
+/// Fields must appear in declaration order. Field content is free, except that it cannot
+/// contain `[` followed by the prefix, so it is never mistaken for a delimiter.
+pub fn to_gbnf(schema: &ASLANSchema, prefix: &str) -> String {
+    let grammar = Grammar::build(schema, prefix);
+    let mut out = String::new();
+    for (name, expr) in &grammar.rules {
+        out.push_str(&format!("{} ::= {}\n", name, render_gbnf(expr)));
+    }
+    out
+}
+
+/// Export a regular expression that matches a whole ASLAN document with the shape of `schema`,
+/// for engines that only support regex constraints. Admits the same documents as [`to_gbnf`].
+pub fn to_regex(schema: &ASLANSchema, prefix: &str) -> String {
+    let grammar = Grammar::build(schema, prefix);
+    render_regex(grammar.get("root"), &grammar)
+}
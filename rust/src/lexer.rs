@@ -0,0 +1,160 @@
+use crate::aslan_parser::{ASLANDelimiterType, ASLANParser, ASLANParserSettings, ASLANSpan};
+use serde::{Deserialize, Serialize};
+
+/// Whether an object or array delimiter opened or closed its block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ASLANBlockEdge {
+    Open,
+    Close,
+}
+
+/// Token kinds produced by `lex`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ASLANTokenKind {
+    /// Text outside of delimiters, including text the parser ignores such as whitespace around blocks
+    Text,
+    /// Text between a pair of escape delimiters
+    EscapeBody,
+    /// Text after a comment delimiter
+    CommentBody,
+    /// A well-formed delimiter with a known suffix
+    Delimiter {
+        prefix: String,
+        delimiter_type: ASLANDelimiterType,
+        content: Option<String>,
+        args: Vec<String>,
+        /// Set for object and array delimiters
+        block: Option<ASLANBlockEdge>,
+    },
+    /// A well-formed delimiter with the suffix of an extension registered with the parser
+    ExtensionDelimiter {
+        prefix: String,
+        suffix: char,
+        content: Option<String>,
+        args: Vec<String>,
+    },
+    /// A well-formed delimiter with a reserved suffix, which the parser drops
+    ReservedDelimiter { prefix: String, suffix: char },
+    /// Text starting with the delimiter prefix that does not form a delimiter. The parser keeps it as data
+    MalformedDelimiter,
+}
+
+/// A token of ASLAN text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ASLANToken {
+    pub kind: ASLANTokenKind,
+    pub span: ASLANSpan,
+    pub text: String,
+}
+
+/// A node of the concrete syntax tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ASLANSyntaxNode {
+    Token(ASLANToken),
+    /// A data delimiter and everything up to the next data delimiter of the same block
+    Field(Vec<ASLANSyntaxNode>),
+    /// An object or array from its opening to its closing delimiter
+    Block(Vec<ASLANSyntaxNode>),
+}
+
+impl ASLANSyntaxNode {
+    fn write_text(&self, out: &mut String) {
+        match self {
+            ASLANSyntaxNode::Token(token) => out.push_str(&token.text),
+            ASLANSyntaxNode::Field(children) | ASLANSyntaxNode::Block(children) => {
+                for child in children {
+                    child.write_text(out);
+                }
+            }
+        }
+    }
+}
+
+/// Concrete syntax tree of ASLAN text, keeping every byte of the input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ASLANSyntaxTree {
+    pub children: Vec<ASLANSyntaxNode>,
+}
+
+impl ASLANSyntaxTree {
+    /// The text the tree was built from
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        for child in &self.children {
+            child.write_text(&mut out);
+        }
+        out
+    }
+
+    /// Build the concrete syntax tree from the tokens of `lex` or `ASLANParser::lex`
+    pub fn from_tokens(tokens: Vec<ASLANToken>) -> Self {
+        #[derive(Default)]
+        struct Frame {
+            children: Vec<ASLANSyntaxNode>,
+            field: Option<Vec<ASLANSyntaxNode>>,
+        }
+
+        impl Frame {
+            fn push(&mut self, node: ASLANSyntaxNode) {
+                match &mut self.field {
+                    Some(field) => field.push(node),
+                    None => self.children.push(node),
+                }
+            }
+
+            fn end_field(&mut self) {
+                if let Some(field) = self.field.take() {
+                    self.children.push(ASLANSyntaxNode::Field(field));
+                }
+            }
+        }
+
+        let mut stack = vec![Frame::default()];
+        for token in tokens {
+            match &token.kind {
+                ASLANTokenKind::Delimiter { delimiter_type: ASLANDelimiterType::Data, .. } => {
+                    let frame = stack.last_mut().unwrap();
+                    frame.end_field();
+                    frame.field = Some(vec![ASLANSyntaxNode::Token(token)]);
+                }
+                ASLANTokenKind::Delimiter { block: Some(ASLANBlockEdge::Open), .. } => {
+                    stack.push(Frame {
+                        children: vec![ASLANSyntaxNode::Token(token)],
+                        field: None,
+                    });
+                }
+                ASLANTokenKind::Delimiter { block: Some(ASLANBlockEdge::Close), .. } if stack.len() > 1 => {
+                    let mut frame = stack.pop().unwrap();
+                    frame.end_field();
+                    frame.children.push(ASLANSyntaxNode::Token(token));
+                    stack.last_mut().unwrap().push(ASLANSyntaxNode::Block(frame.children));
+                }
+                _ => stack.last_mut().unwrap().push(ASLANSyntaxNode::Token(token)),
+            }
+        }
+
+        // Unclosed blocks end with the input
+        while stack.len() > 1 {
+            let mut frame = stack.pop().unwrap();
+            frame.end_field();
+            stack.last_mut().unwrap().push(ASLANSyntaxNode::Block(frame.children));
+        }
+        let mut root = stack.pop().unwrap();
+        root.end_field();
+        Self { children: root.children }
+    }
+}
+
+/// Split ASLAN text into tokens. Concatenating the token texts gives back the input.
+///
+/// Tokens come from the parser's own state machine, so they always agree with how an
+/// `ASLANParser` with the same settings reads the same text. Use `ASLANParser::lex` to also lex
+/// the delimiters of registered extensions.
+pub fn lex(text: &str, settings: &ASLANParserSettings) -> Vec<ASLANToken> {
+    ASLANParser::with_settings(settings.clone()).lex(text)
+}
+
+/// Build the concrete syntax tree of ASLAN text
+pub fn syntax_tree(text: &str, settings: &ASLANParserSettings) -> ASLANSyntaxTree {
+    ASLANSyntaxTree::from_tokens(lex(text, settings))
+}
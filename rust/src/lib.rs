@@ -1,7 +1,34 @@
 pub mod aslan_parser;
+pub mod diagnostics;
+pub mod error;
+pub mod extension;
+pub mod generator;
+pub mod grammar;
+pub mod lexer;
+pub mod prompt;
 pub mod recent_items;
+pub mod rich_text;
+pub mod router;
+pub mod schema;
+pub mod settings;
 pub mod utils;
 
 pub use aslan_parser::*;
+pub use diagnostics::{ASLANDiagnostic, ASLANDiagnosticCode, ASLANDiagnosticSeverity};
+pub use error::{ASLANError, ASLANSettingsError};
+pub use extension::{
+    ASLANDelimiterExtension, ASLANExtensionContent, ASLANExtensionContext, ASLANExtensionDelimiter, ASLANExtensionEvent,
+    ASLANExtensionGrammar,
+};
+pub use lexer::{lex, syntax_tree, ASLANBlockEdge, ASLANSyntaxNode, ASLANSyntaxTree, ASLANToken, ASLANTokenKind};
 pub use recent_items::RecentItems;
+pub use rich_text::{ASLANRichText, ASLANRichTextNode, ASLANRichTextPart};
+pub use router::ASLANPrefixRouter;
+pub use schema::{
+    ASLANSchema, ASLANSchemaError, ASLANSchemaField, ASLANSchemaType, ASLANViolation, ASLANViolationKind, AslanSchema,
+};
+pub use settings::ASLANParserSettingsBuilder;
 pub use utils::*;
+
+#[cfg(feature = "derive")]
+pub use aslan_derive::AslanSchema;
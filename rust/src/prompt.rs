@@ -0,0 +1,166 @@
+use crate::aslan_parser::ASLANParserSettings;
+use crate::schema::{ASLANSchema, ASLANSchemaType};
+
+/// The ASLAN features a schema makes use of
+#[derive(Debug, Default)]
+struct UsedFeatures {
+    objects: bool,
+    arrays: bool,
+    parts: bool,
+    instructions: Vec<String>,
+    /// Whether instructions are allowed on parts fields and on plain text fields
+    part_instructions: bool,
+    text_instructions: bool,
+    voids: bool,
+    escapes: bool,
+    duplicate_args: bool,
+}
+
+impl UsedFeatures {
+    fn collect(schema: &ASLANSchema, root: bool, features: &mut UsedFeatures) {
+        match schema.schema_type {
+            ASLANSchemaType::Object if !root => features.objects = true,
+            ASLANSchemaType::Array => features.arrays = true,
+            ASLANSchemaType::Parts => features.parts = true,
+            _ => {}
+        }
+        features.voids |= schema.nullable;
+        features.escapes |= schema.escaped;
+        if schema.allowed_instructions.as_ref().is_some_and(|i| !i.is_empty()) {
+            match schema.schema_type {
+                ASLANSchemaType::Parts => features.part_instructions = true,
+                _ => features.text_instructions = true,
+            }
+        }
+        for instruction in schema.allowed_instructions.iter().flatten() {
+            if !features.instructions.contains(instruction) {
+                features.instructions.push(instruction.clone());
+            }
+        }
+        for field in &schema.fields {
+            features.duplicate_args |= field.duplicate_behavior.is_some();
+            Self::collect(&field.schema, false, features);
+        }
+        if let Some(items) = &schema.items {
+            Self::collect(items, false, features);
+        }
+    }
+}
+
+/// Generate system prompt text teaching an LLM to write ASLAN with the given shape.
+///
+/// Only the delimiters the schema needs are explained, using the prefix and default field
+/// name from `settings`. `go` and `stop` are explained when `strict_start` or `strict_end` is enabled.
+pub fn describe(schema: &ASLANSchema, settings: &ASLANParserSettings) -> String {
+    let prefix = &settings.prefix;
+    let mut features = UsedFeatures::default();
+    UsedFeatures::collect(schema, true, &mut features);
+
+    let mut rules = Vec::new();
+    if settings.strict_start {
+        rules.push(format!(
+            "Start your structured answer with `[{}g]`. Anything before it is ignored.",
+            prefix
+        ));
+    }
+    if schema.schema_type == ASLANSchemaType::Object {
+        rules.push(format!(
+            "`[{}d_NAME]` starts the field NAME. Its value is all the text that follows until the next delimiter, so do not add separators between fields.",
+            prefix
+        ));
+        if !settings.strict_start {
+            rules.push(format!(
+                "Text before the first field is stored as `{}` and ignored, so start directly with a field.",
+                settings.default_field_name
+            ));
+        }
+    } else {
+        rules.push(format!(
+            "Write the value directly. It is stored as `{}`.",
+            settings.default_field_name
+        ));
+    }
+    if features.duplicate_args {
+        rules.push(
+            "Keep the `:a`, `:f` and `:l` arguments shown in field delimiters. They tell the parser to append repeated values, or keep the first or last one."
+                .to_string(),
+        );
+    }
+    if features.objects {
+        rules.push(format!(
+            "`[{}o]` right after a field delimiter opens a nested object. Write its fields, then close it with another `[{}o]`.",
+            prefix, prefix
+        ));
+    }
+    if features.arrays {
+        rules.push(format!(
+            "`[{}a]` right after a field delimiter opens a list. Start each item with `[{}d]` and close the list with another `[{}a]`.",
+            prefix, prefix, prefix
+        ));
+    }
+    if features.parts {
+        rules.push(format!("`[{}p]` splits a text value into separate parts.", prefix));
+    }
+    if !features.instructions.is_empty() {
+        let names: Vec<String> = features.instructions.iter().map(|i| format!("`{}`", i)).collect();
+        let placement = match (features.part_instructions, features.text_instructions) {
+            (true, false) => format!("right after `[{}p]` applies the instruction NAME to that part", prefix),
+            (false, _) => "inside the field's text applies the instruction NAME at that point".to_string(),
+            (true, true) => format!(
+                "right after `[{}p]` applies the instruction NAME to that part. In fields without parts, write it inside the field's text",
+                prefix
+            ),
+        };
+        rules.push(format!(
+            "`[{}i_NAME]` {}. Available instructions: {}.",
+            prefix,
+            placement,
+            names.join(", ")
+        ));
+    }
+    if features.voids {
+        rules.push(format!(
+            "`[{}v]` as the whole value of a field marks it as empty. Only use it where a field may be void.",
+            prefix
+        ));
+    }
+    if features.escapes {
+        rules.push(format!(
+            "Wrap escaped values in `[{}e_TAG]` and `[{}e_TAG]`, choosing a TAG that does not appear in the value. Everything between them is kept as plain text, including square brackets.",
+            prefix, prefix
+        ));
+    }
+    if settings.strict_end {
+        rules.push(format!(
+            "End your structured answer with `[{}s]`. Anything after it is ignored.",
+            prefix
+        ));
+    }
+
+    let mut example = String::new();
+    if settings.strict_start {
+        example.push_str(&format!("[{}g]", prefix));
+    }
+    example.push_str(&schema.to_example(prefix));
+    if settings.strict_end {
+        example.push_str(&format!("[{}s]", prefix));
+    }
+
+    let mut out = String::from(
+        "Format your answer as ASLAN. ASLAN structures text with delimiters written in square brackets.\n\nRules:\n",
+    );
+    for rule in rules {
+        out.push_str(&format!("- {}\n", rule));
+    }
+    let format = schema.describe_format();
+    if !format.is_empty() {
+        out.push_str("\nExpected format:\n");
+        out.push_str(&format);
+    }
+    out.push_str("\nExample:\n");
+    out.push_str(&example);
+    if !example.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
@@ -59,12 +59,7 @@ impl<T> RecentItems<T> {
     where
         T: Eq + Hash,
     {
-        for item in &self.items {
-            if !excluded_set.contains(item) {
-                return Some(item);
-            }
-        }
-        None
+        self.items.iter().find(|item| !excluded_set.contains(item))
     }
 
     /// Get the nth most recently added item that is not in the excluded set (1-indexed).
@@ -0,0 +1,173 @@
+use crate::aslan_parser::{ASLANContentPart, ASLANEndDataInstruction, ASLANIndexUnit, ASLANInstructionInfo};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// A node of the rich text of a part
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ASLANRichTextNode {
+    Text(String),
+    /// A point instruction at its index
+    Instruction {
+        name: String,
+        args: Vec<String>,
+        named_args: HashMap<String, String>,
+    },
+    /// A ranged instruction around the text it covers. A range overlapping the end of an outer
+    /// range is split into one node inside and one after it
+    Range {
+        name: String,
+        args: Vec<String>,
+        named_args: HashMap<String, String>,
+        children: Vec<ASLANRichTextNode>,
+    },
+}
+
+impl ASLANRichTextNode {
+    fn instruction(instruction: &ASLANInstructionInfo) -> Self {
+        ASLANRichTextNode::Instruction {
+            name: instruction.name.clone(),
+            args: instruction.args.clone(),
+            named_args: instruction.named_args.clone(),
+        }
+    }
+
+    fn range(instruction: &ASLANInstructionInfo, children: Vec<ASLANRichTextNode>) -> Self {
+        ASLANRichTextNode::Range {
+            name: instruction.name.clone(),
+            args: instruction.args.clone(),
+            named_args: instruction.named_args.clone(),
+            children,
+        }
+    }
+}
+
+/// A part of a field as rich text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ASLANRichTextPart {
+    pub part_index: usize,
+    pub text: String,
+    /// Instructions of the part with their indices and ranges
+    pub instructions: Vec<ASLANInstructionInfo>,
+    /// The text with point instructions at their index and ranged instructions around their text
+    pub children: Vec<ASLANRichTextNode>,
+}
+
+/// A string or part field as rich text, so clients don't have to place instructions themselves
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ASLANRichText {
+    pub parts: Vec<ASLANRichTextPart>,
+}
+
+impl ASLANRichText {
+    /// Build the rich text of the field of an end_data event, in the index unit of the event
+    pub fn from_end_data(event: &ASLANEndDataInstruction) -> Self {
+        Self::from_parts(&event.content, event.index_unit)
+    }
+
+    /// Build rich text from the parts of a completed field, with indices counted in `unit`
+    pub fn from_parts(parts: &[ASLANContentPart], unit: ASLANIndexUnit) -> Self {
+        Self {
+            parts: parts.iter().map(|part| build_part(part, unit)).collect(),
+        }
+    }
+
+    /// Build rich text without instructions from a string or part field of a result.
+    /// Returns `None` for other values
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let values: Vec<&str> = match value {
+            Value::String(text) => vec![text],
+            Value::Array(parts) => parts.iter().map(Value::as_str).collect::<Option<_>>()?,
+            _ => return None,
+        };
+        let parts: Vec<ASLANContentPart> = values
+            .into_iter()
+            .enumerate()
+            .map(|(part_index, text)| ASLANContentPart {
+                value: text.to_string(),
+                part_index,
+                instructions: Vec::new(),
+            })
+            .collect();
+        Some(Self::from_parts(&parts, ASLANIndexUnit::Bytes))
+    }
+}
+
+fn build_part(part: &ASLANContentPart, unit: ASLANIndexUnit) -> ASLANRichTextPart {
+    let text = &part.value;
+    let length = unit.length(text);
+    let clamp = |(start, end): (usize, usize)| (start.min(length), end.min(length).max(start.min(length)));
+
+    // Ranges covering text, outermost first when they start together
+    let mut ranges: Vec<(usize, usize, usize)> = part
+        .instructions
+        .iter()
+        .enumerate()
+        .filter_map(|(i, instruction)| instruction.range.map(clamp).map(|(start, end)| (start, end, i)))
+        .filter(|(start, end, _)| start < end)
+        .collect();
+    ranges.sort_by_key(|&(start, end, i)| (start, Reverse(end), i));
+
+    let mut bounds: Vec<usize> = vec![0, length];
+    bounds.extend(ranges.iter().flat_map(|&(start, end, _)| [start, end]));
+    bounds.extend(part.instructions.iter().map(|instruction| match instruction.range {
+        Some(range) => clamp(range).0,
+        None => instruction.index.min(length),
+    }));
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    // Open ranges as positions in `ranges`, each with the nodes collected so far
+    let mut stack: Vec<(Option<usize>, Vec<ASLANRichTextNode>)> = vec![(None, Vec::new())];
+    for (k, &at) in bounds.iter().enumerate() {
+        let next = bounds.get(k + 1).copied();
+        let active: Vec<usize> = match next {
+            Some(_) => (0..ranges.len()).filter(|&r| ranges[r].0 <= at && ranges[r].1 > at).collect(),
+            None => Vec::new(),
+        };
+
+        // Close ranges that end here, and reopen ranges opened after them later
+        let kept = stack[1..].iter().zip(&active).take_while(|((open, _), r)| *open == Some(**r)).count();
+        while stack.len() > kept + 1 {
+            let (open, children) = stack.pop().unwrap();
+            let node = ASLANRichTextNode::range(&part.instructions[ranges[open.unwrap()].2], children);
+            stack.last_mut().unwrap().1.push(node);
+        }
+
+        // Points and empty ranges go before ranges opening at the same index
+        for instruction in &part.instructions {
+            let node = match instruction.range.map(clamp) {
+                None if instruction.index.min(length) == at => ASLANRichTextNode::instruction(instruction),
+                Some((start, end)) if start == at && start == end => ASLANRichTextNode::range(instruction, Vec::new()),
+                _ => continue,
+            };
+            stack.last_mut().unwrap().1.push(node);
+        }
+
+        for &r in &active[kept..] {
+            stack.push((Some(r), Vec::new()));
+        }
+        if let Some(next) = next {
+            let slice = &text[unit.byte_offset(text, at)..unit.byte_offset(text, next)];
+            push_text(&mut stack.last_mut().unwrap().1, slice);
+        }
+    }
+
+    ASLANRichTextPart {
+        part_index: part.part_index,
+        text: text.clone(),
+        instructions: part.instructions.clone(),
+        children: stack.pop().unwrap().1,
+    }
+}
+
+fn push_text(children: &mut Vec<ASLANRichTextNode>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match children.last_mut() {
+        Some(ASLANRichTextNode::Text(last)) => last.push_str(text),
+        _ => children.push(ASLANRichTextNode::Text(text.to_string())),
+    }
+}
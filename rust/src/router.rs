@@ -0,0 +1,127 @@
+use crate::aslan_parser::{ASLANParser, ASLANParserSettings};
+use crate::lexer::{ASLANToken, ASLANTokenKind};
+use serde_json::{Map, Value};
+
+/// Routes a stream carrying several prefixes into one parser per prefix, so each namespace gets
+/// its own result, e.g. an agent's own output in `aslan` and quoted sub-agent output in `llm`.
+///
+/// Each delimiter goes to the parser of its prefix, and text goes to the parser of the most recent
+/// delimiter. Text before the first delimiter goes to the first prefix. Offsets in the spans and
+/// diagnostics of each parser only count the input routed to it.
+pub struct ASLANPrefixRouter {
+    scanner: ASLANParser,
+    parsers: Vec<(String, ASLANParser)>,
+    active: usize,
+    multi_aslan_output: bool,
+}
+
+impl ASLANPrefixRouter {
+    /// Create a router with default settings for each prefix
+    pub fn new(prefixes: &[&str]) -> Self {
+        Self::with_settings(ASLANParserSettings::default(), prefixes)
+    }
+
+    /// Create a router giving each prefix's parser a copy of `settings` with its own prefix.
+    /// Falls back to `settings.prefix` when `prefixes` is empty.
+    pub fn with_settings(settings: ASLANParserSettings, prefixes: &[&str]) -> Self {
+        let mut prefixes: Vec<String> = prefixes.iter().map(|prefix| prefix.to_string()).collect();
+        if prefixes.is_empty() {
+            prefixes.push(settings.prefix.clone());
+        }
+        // The scanner reads delimiters like the prefix parsers do, but recognizes every prefix
+        let scanner = ASLANParser::with_settings(ASLANParserSettings {
+            prefix: prefixes[0].clone(),
+            additional_prefixes: prefixes[1..].to_vec(),
+            detect_prefix: false,
+            ..settings.clone()
+        });
+        let parsers = prefixes
+            .into_iter()
+            .map(|prefix| {
+                let parser = ASLANParser::with_settings(ASLANParserSettings {
+                    prefix: prefix.clone(),
+                    additional_prefixes: Vec::new(),
+                    ..settings.clone()
+                });
+                (prefix, parser)
+            })
+            .collect();
+        Self {
+            scanner,
+            parsers,
+            active: 0,
+            multi_aslan_output: settings.multi_aslan_output,
+        }
+    }
+
+    /// Parse a complete input string and return the results keyed by prefix
+    pub fn parse(&mut self, input: &str) -> Value {
+        self.parse_next(input);
+        self.close();
+        self.get_results()
+    }
+
+    /// Parse input incrementally (streaming)
+    pub fn parse_next(&mut self, input: &str) {
+        let tokens = self.scanner.lex_next(input);
+        self.route(tokens);
+    }
+
+    /// Close every parser and finalize results
+    pub fn close(&mut self) {
+        let tokens = self.scanner.lex_close();
+        self.route(tokens);
+        for (_, parser) in &mut self.parsers {
+            parser.close();
+        }
+    }
+
+    fn route(&mut self, tokens: Vec<ASLANToken>) {
+        for token in tokens {
+            if let ASLANTokenKind::Delimiter { prefix, .. } | ASLANTokenKind::ReservedDelimiter { prefix, .. } = &token.kind {
+                if let Some(index) = self.parsers.iter().position(|(p, _)| p == prefix) {
+                    self.active = index;
+                }
+            }
+            self.parsers[self.active].1.parse_next(&token.text);
+        }
+    }
+
+    /// The prefix text is currently routed to
+    pub fn active_prefix(&self) -> &str {
+        &self.parsers[self.active].0
+    }
+
+    /// The parser of a prefix, e.g. to read its violations
+    pub fn parser(&self, prefix: &str) -> Option<&ASLANParser> {
+        self.parsers.iter().find(|(p, _)| p == prefix).map(|(_, parser)| parser)
+    }
+
+    /// The parser of a prefix, e.g. to add event listeners
+    pub fn parser_mut(&mut self, prefix: &str) -> Option<&mut ASLANParser> {
+        self.parsers.iter_mut().find(|(p, _)| p == prefix).map(|(_, parser)| parser)
+    }
+
+    /// Get the result of a prefix
+    pub fn get_result(&self, prefix: &str) -> Option<Value> {
+        self.parser(prefix).map(|parser| self.result_of(parser))
+    }
+
+    /// Get the results of all prefixes keyed by prefix
+    pub fn get_results(&self) -> Value {
+        let results: Map<String, Value> = self
+            .parsers
+            .iter()
+            .map(|(prefix, parser)| (prefix.clone(), self.result_of(parser)))
+            .collect();
+        Value::Object(results)
+    }
+
+    fn result_of(&self, parser: &ASLANParser) -> Value {
+        if self.multi_aslan_output {
+            Value::Array(parser.get_results())
+        } else {
+            parser.get_result()
+        }
+    }
+}
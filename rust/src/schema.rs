@@ -0,0 +1,1163 @@
+use crate::aslan_parser::{ASLANDuplicateKeyBehavior, ASLANEndDataInstruction};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Borrow;
+use std::fmt;
+
+/// Name of the default field the parser inserts at the root of the result for text before the
+/// first delimiter. Validation never reports it as an unknown field.
+const DEFAULT_FIELD_NAME: &str = "_default";
+
+/// The expected type of a node in an ASLAN result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ASLANSchemaType {
+    /// A plain string field
+    String,
+    /// A string field that may be split with part delimiters
+    Parts,
+    Object,
+    Array,
+    /// A string field holding an integer, coerced when `coerce_leaves` is enabled
+    Integer,
+    /// A string field holding a number, coerced when `coerce_leaves` is enabled
+    Number,
+    /// A string field holding `true` or `false`, coerced when `coerce_leaves` is enabled
+    Boolean,
+}
+
+impl ASLANSchemaType {
+    /// Whether values of this type are written as a single ASLAN string
+    pub fn is_scalar(&self) -> bool {
+        matches!(
+            self,
+            ASLANSchemaType::String | ASLANSchemaType::Integer | ASLANSchemaType::Number | ASLANSchemaType::Boolean
+        )
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ASLANSchemaType::String => "string",
+            ASLANSchemaType::Parts => "string or parts",
+            ASLANSchemaType::Object => "object",
+            ASLANSchemaType::Array => "array",
+            ASLANSchemaType::Integer => "integer",
+            ASLANSchemaType::Number => "number",
+            ASLANSchemaType::Boolean => "boolean",
+        }
+    }
+
+    /// Trim and convert a raw ASLAN string into a JSON value of this type.
+    /// Returns `None` if the text cannot be represented as this type.
+    pub fn coerce(&self, raw: &str) -> Option<Value> {
+        let trimmed = raw.trim();
+        match self {
+            ASLANSchemaType::Integer => trimmed.parse::<i64>().ok().map(Value::from),
+            ASLANSchemaType::Number => {
+                if let Ok(i) = trimmed.parse::<i64>() {
+                    return Some(Value::from(i));
+                }
+                trimmed
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(Value::Number)
+            }
+            ASLANSchemaType::Boolean => match trimmed.to_ascii_lowercase().as_str() {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ASLANSchemaType::String | ASLANSchemaType::Parts => Some(Value::String(raw.to_string())),
+            ASLANSchemaType::Object | ASLANSchemaType::Array => None,
+        }
+    }
+}
+
+/// A named field of an object schema
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ASLANSchemaField {
+    pub name: String,
+    #[serde(default)]
+    pub schema: ASLANSchema,
+    /// Fields are required unless stated otherwise, like with `ASLANSchema::with_field`
+    #[serde(default = "default_required")]
+    pub required: bool,
+    /// Duplicate behavior the LLM should declare on the field's data delimiter
+    #[serde(default)]
+    pub duplicate_behavior: Option<ASLANDuplicateKeyBehavior>,
+    /// Share of observed objects containing this field, set by `infer`
+    #[serde(default)]
+    pub frequency: Option<f64>,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+impl ASLANSchemaField {
+    /// Create a field without a duplicate behavior
+    pub fn new(name: &str, schema: ASLANSchema, required: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            schema,
+            required,
+            duplicate_behavior: None,
+            frequency: None,
+        }
+    }
+}
+
+/// Describes the expected shape of an ASLAN result. Omitted keys take their `Default` value
+/// when deserializing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ASLANSchema {
+    pub schema_type: ASLANSchemaType,
+    pub nullable: bool,
+    pub fields: Vec<ASLANSchemaField>,
+    pub additional_fields: bool,
+    pub items: Option<Box<ASLANSchema>>,
+    pub min_items: Option<usize>,
+    pub max_items: Option<usize>,
+    pub allowed_values: Option<Vec<String>>,
+    pub allowed_instructions: Option<Vec<String>>,
+    /// The value may contain text that looks like ASLAN delimiters and should be escaped
+    pub escaped: bool,
+    pub description: Option<String>,
+}
+
+impl Default for ASLANSchema {
+    fn default() -> Self {
+        Self {
+            schema_type: ASLANSchemaType::String,
+            nullable: false,
+            fields: Vec::new(),
+            additional_fields: true,
+            items: None,
+            min_items: None,
+            max_items: None,
+            allowed_values: None,
+            allowed_instructions: None,
+            escaped: false,
+            description: None,
+        }
+    }
+}
+
+/// Kinds of schema violations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ASLANViolationKind {
+    MissingField,
+    UnknownField,
+    TypeMismatch,
+    UnexpectedNull,
+    TooFewItems,
+    TooManyItems,
+    DisallowedValue,
+    DisallowedInstruction,
+    CoercionFailed,
+}
+
+/// A single schema violation found in a result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ASLANViolation {
+    pub kind: ASLANViolationKind,
+    pub path: Vec<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ASLANViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "<root>: {}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path.join("."), self.message)
+        }
+    }
+}
+
+/// Error returned when a JSON Schema cannot be converted to an ASLAN schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct ASLANSchemaError {
+    pub path: Vec<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ASLANSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "invalid schema: {}", self.message)
+        } else {
+            write!(f, "invalid schema at {}: {}", self.path.join("."), self.message)
+        }
+    }
+}
+
+impl std::error::Error for ASLANSchemaError {}
+
+impl ASLANSchema {
+    /// Create a schema for a plain string field
+    pub fn string() -> Self {
+        Self::default()
+    }
+
+    /// Create a schema for a string field that may contain parts
+    pub fn parts() -> Self {
+        Self {
+            schema_type: ASLANSchemaType::Parts,
+            ..Default::default()
+        }
+    }
+
+    /// Create a schema for an object with no declared fields
+    pub fn object() -> Self {
+        Self {
+            schema_type: ASLANSchemaType::Object,
+            ..Default::default()
+        }
+    }
+
+    /// Create a schema for a leaf of the given scalar type
+    pub fn scalar(schema_type: ASLANSchemaType) -> Self {
+        Self {
+            schema_type,
+            ..Default::default()
+        }
+    }
+
+    /// Create a schema for an array whose items match `items`
+    pub fn array(items: ASLANSchema) -> Self {
+        Self {
+            schema_type: ASLANSchemaType::Array,
+            items: Some(Box::new(items)),
+            ..Default::default()
+        }
+    }
+
+    /// Add a required field to an object schema
+    pub fn with_field(mut self, name: &str, schema: ASLANSchema) -> Self {
+        self.fields.push(ASLANSchemaField::new(name, schema, true));
+        self
+    }
+
+    /// Add an optional field to an object schema
+    pub fn with_optional_field(mut self, name: &str, schema: ASLANSchema) -> Self {
+        self.fields.push(ASLANSchemaField::new(name, schema, false));
+        self
+    }
+
+    /// Disallow fields that are not declared in this object schema
+    pub fn closed(mut self) -> Self {
+        self.additional_fields = false;
+        self
+    }
+
+    /// Allow the value to be void
+    pub fn nullable(mut self) -> Self {
+        self.nullable = true;
+        self
+    }
+
+    /// Limit the number of items in an array schema
+    pub fn with_item_bounds(mut self, min_items: Option<usize>, max_items: Option<usize>) -> Self {
+        self.min_items = min_items;
+        self.max_items = max_items;
+        self
+    }
+
+    /// Restrict a string field to a fixed set of values
+    pub fn with_allowed_values(mut self, values: &[&str]) -> Self {
+        self.allowed_values = Some(values.iter().map(|v| v.to_string()).collect());
+        self
+    }
+
+    /// Restrict which instructions may appear in this field
+    pub fn with_instructions(mut self, instructions: &[&str]) -> Self {
+        self.allowed_instructions = Some(instructions.iter().map(|i| i.to_string()).collect());
+        self
+    }
+
+    /// Mark a string value as one that should be wrapped in an escape
+    pub fn escaped(mut self) -> Self {
+        self.escaped = true;
+        self
+    }
+
+    /// Attach a human-readable description
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Get the declared field with the given name
+    pub fn get_field(&self, name: &str) -> Option<&ASLANSchemaField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Resolve the schema of the node at `path`, where each entry is an object key or array index.
+    /// Returns `None` when the path leaves the declared structure.
+    pub fn resolve(&self, path: &[String]) -> Option<&ASLANSchema> {
+        let mut schema = self;
+        for key in path {
+            schema = match schema.schema_type {
+                ASLANSchemaType::Object => &schema.get_field(key)?.schema,
+                ASLANSchemaType::Array => schema.items.as_deref()?,
+                _ => return None,
+            };
+        }
+        Some(schema)
+    }
+
+    /// Load a schema from the supported subset of JSON Schema.
+    ///
+    /// Supported keywords are `type` (including `"null"` in type arrays), `nullable`,
+    /// `properties`, `required`, `additionalProperties`, `items`, `minItems`, `maxItems`,
+    /// `enum`, `description` and `anyOf`/`oneOf` with a single non-null branch.
+    /// The ASLAN specific keywords `x-aslan-parts`, `x-aslan-instructions` and `x-aslan-escaped`
+    /// mark part fields, the instructions allowed in a field and fields that should be escaped.
+    pub fn from_json_schema(json_schema: &Value) -> Result<Self, ASLANSchemaError> {
+        let mut path = Vec::new();
+        Self::from_json_schema_at(json_schema, &mut path)
+    }
+
+    fn from_json_schema_at(json_schema: &Value, path: &mut Vec<String>) -> Result<Self, ASLANSchemaError> {
+        let obj = match json_schema {
+            Value::Object(obj) => obj,
+            // Spec: `true` accepts anything, which for ASLAN leaves means a string
+            Value::Bool(true) => return Ok(Self::string()),
+            _ => return Err(schema_error(path, "expected a JSON Schema object")),
+        };
+
+        // Unwrap the common `anyOf: [<schema>, {"type": "null"}]` nullable pattern
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(branches) = obj.get(keyword) {
+                let branches = branches
+                    .as_array()
+                    .ok_or_else(|| schema_error(path, &format!("`{}` must be an array", keyword)))?;
+                let is_null_branch = |b: &Value| b.get("type").and_then(|t| t.as_str()) == Some("null");
+                let non_null: Vec<&Value> = branches.iter().filter(|b| !is_null_branch(b)).collect();
+                if non_null.len() != 1 {
+                    return Err(schema_error(
+                        path,
+                        &format!("`{}` is only supported with a single non-null branch", keyword),
+                    ));
+                }
+                let mut schema = Self::from_json_schema_at(non_null[0], path)?;
+                schema.nullable = schema.nullable || non_null.len() < branches.len();
+                if let Some(description) = obj.get("description").and_then(|d| d.as_str()) {
+                    schema.description = Some(description.to_string());
+                }
+                return Ok(schema);
+            }
+        }
+
+        let mut nullable = obj.get("nullable").and_then(|n| n.as_bool()).unwrap_or(false);
+        let mut type_name: Option<String> = None;
+        match obj.get("type") {
+            Some(Value::String(t)) => type_name = Some(t.clone()),
+            Some(Value::Array(types)) => {
+                for t in types {
+                    match t.as_str() {
+                        Some("null") => nullable = true,
+                        Some(t) if type_name.is_none() => type_name = Some(t.to_string()),
+                        Some(_) => {
+                            return Err(schema_error(path, "multiple non-null types are not supported"));
+                        }
+                        None => return Err(schema_error(path, "`type` entries must be strings")),
+                    }
+                }
+            }
+            Some(_) => return Err(schema_error(path, "`type` must be a string or an array of strings")),
+            None => {}
+        }
+        let type_name = type_name.unwrap_or_else(|| {
+            if obj.contains_key("properties") {
+                "object".to_string()
+            } else if obj.contains_key("items") {
+                "array".to_string()
+            } else {
+                "string".to_string()
+            }
+        });
+
+        let is_parts = obj.get("x-aslan-parts").and_then(|p| p.as_bool()).unwrap_or(false);
+        let mut schema = match type_name.as_str() {
+            "string" | "integer" | "number" | "boolean" if is_parts => Self::parts(),
+            "string" => Self::string(),
+            "integer" => Self::scalar(ASLANSchemaType::Integer),
+            "number" => Self::scalar(ASLANSchemaType::Number),
+            "boolean" => Self::scalar(ASLANSchemaType::Boolean),
+            "array" if is_parts => Self::parts(),
+            "array" => {
+                let items = match obj.get("items") {
+                    Some(items) => {
+                        path.push("items".to_string());
+                        let items = Self::from_json_schema_at(items, path)?;
+                        path.pop();
+                        items
+                    }
+                    None => Self::string(),
+                };
+                let mut schema = Self::array(items);
+                schema.min_items = get_usize(obj.get("minItems"), path, "minItems")?;
+                schema.max_items = get_usize(obj.get("maxItems"), path, "maxItems")?;
+                schema
+            }
+            "object" => {
+                let mut schema = Self::object();
+                let required: Vec<&str> = match obj.get("required") {
+                    Some(Value::Array(required)) => required.iter().filter_map(|r| r.as_str()).collect(),
+                    Some(_) => return Err(schema_error(path, "`required` must be an array")),
+                    None => Vec::new(),
+                };
+                if let Some(properties) = obj.get("properties") {
+                    let properties = properties
+                        .as_object()
+                        .ok_or_else(|| schema_error(path, "`properties` must be an object"))?;
+                    for (name, property) in properties {
+                        path.push(name.clone());
+                        let field_schema = Self::from_json_schema_at(property, path)?;
+                        path.pop();
+                        schema.fields.push(ASLANSchemaField {
+                            name: name.clone(),
+                            schema: field_schema,
+                            required: required.contains(&name.as_str()),
+                            duplicate_behavior: None,
+                            frequency: None,
+                        });
+                    }
+                }
+                match obj.get("additionalProperties") {
+                    Some(Value::Bool(allowed)) => schema.additional_fields = *allowed,
+                    Some(Value::Object(_)) | None => {}
+                    Some(_) => return Err(schema_error(path, "`additionalProperties` must be a boolean or object")),
+                }
+                schema
+            }
+            "null" => {
+                nullable = true;
+                Self::string()
+            }
+            other => return Err(schema_error(path, &format!("unsupported type `{}`", other))),
+        };
+
+        schema.nullable = nullable;
+        if let Some(values) = obj.get("enum") {
+            let values = values
+                .as_array()
+                .ok_or_else(|| schema_error(path, "`enum` must be an array"))?;
+            let mut allowed = Vec::new();
+            for value in values {
+                match value {
+                    Value::String(s) => allowed.push(s.clone()),
+                    Value::Null => schema.nullable = true,
+                    // Non-string enum values are matched against their ASLAN string form
+                    other => allowed.push(other.to_string()),
+                }
+            }
+            schema.allowed_values = Some(allowed);
+        }
+        if let Some(instructions) = obj.get("x-aslan-instructions") {
+            let instructions = instructions
+                .as_array()
+                .ok_or_else(|| schema_error(path, "`x-aslan-instructions` must be an array"))?;
+            schema.allowed_instructions = Some(
+                instructions
+                    .iter()
+                    .filter_map(|i| i.as_str().map(|s| s.to_string()))
+                    .collect(),
+            );
+        }
+        schema.escaped = obj.get("x-aslan-escaped").and_then(|e| e.as_bool()).unwrap_or(false);
+        if let Some(description) = obj.get("description").and_then(|d| d.as_str()) {
+            schema.description = Some(description.to_string());
+        }
+        Ok(schema)
+    }
+
+    /// Validate a parse result against this schema.
+    /// The `_default` field is always permitted in objects. Instructions are not part of the
+    /// result, check them with `validate_end_data`.
+    pub fn validate(&self, value: &Value) -> Vec<ASLANViolation> {
+        self.validate_with_default_field(value, DEFAULT_FIELD_NAME)
+    }
+
+    /// Validate a parse result produced with a renamed default field
+    pub fn validate_with_default_field(&self, value: &Value, default_field_name: &str) -> Vec<ASLANViolation> {
+        let mut violations = Vec::new();
+        let mut path = Vec::new();
+        self.validate_at(value, default_field_name, &mut path, &mut violations);
+        violations
+    }
+
+    /// Check the instructions in the parts of a completed field against `allowed_instructions`
+    /// of the field's schema
+    pub fn validate_end_data(&self, event: &ASLANEndDataInstruction) -> Vec<ASLANViolation> {
+        let allowed = match self.resolve(&event.path).and_then(|schema| schema.allowed_instructions.as_ref()) {
+            Some(allowed) => allowed,
+            None => return Vec::new(),
+        };
+        event
+            .content
+            .iter()
+            .flat_map(|part| &part.instructions)
+            .filter(|instruction| !allowed.contains(&instruction.name))
+            .map(|instruction| {
+                let message = format!("instruction `{}` is not one of {:?}", instruction.name, allowed);
+                violation(ASLANViolationKind::DisallowedInstruction, &event.path, &message)
+            })
+            .collect()
+    }
+
+    fn validate_at(
+        &self,
+        value: &Value,
+        default_field_name: &str,
+        path: &mut Vec<String>,
+        violations: &mut Vec<ASLANViolation>,
+    ) {
+        if value.is_null() {
+            if !self.nullable {
+                violations.push(violation(ASLANViolationKind::UnexpectedNull, path, "value is void but the schema does not allow null"));
+            }
+            return;
+        }
+
+        match self.schema_type {
+            ASLANSchemaType::String => match value.as_str() {
+                Some(s) => self.validate_allowed_value(s, path, violations),
+                None => violations.push(type_mismatch(self.schema_type, value, path)),
+            },
+            ASLANSchemaType::Integer | ASLANSchemaType::Number | ASLANSchemaType::Boolean => {
+                let is_coerced = match (self.schema_type, value) {
+                    (ASLANSchemaType::Integer, Value::Number(n)) => n.is_i64() || n.is_u64(),
+                    (ASLANSchemaType::Number, Value::Number(_)) => true,
+                    (ASLANSchemaType::Boolean, Value::Bool(_)) => true,
+                    _ => false,
+                };
+                match value {
+                    _ if is_coerced => self.validate_allowed_value(&value.to_string(), path, violations),
+                    Value::String(s) => match self.schema_type.coerce(s) {
+                        Some(coerced) => self.validate_allowed_value(&coerced.to_string(), path, violations),
+                        None => violations.push(coercion_failed(self.schema_type, s, path)),
+                    },
+                    _ => violations.push(type_mismatch(self.schema_type, value, path)),
+                }
+            }
+            ASLANSchemaType::Parts => match value {
+                Value::String(s) => self.validate_allowed_value(s, path, violations),
+                Value::Array(parts) => {
+                    for (i, part) in parts.iter().enumerate() {
+                        if !part.is_string() {
+                            path.push(i.to_string());
+                            violations.push(type_mismatch(ASLANSchemaType::String, part, path));
+                            path.pop();
+                        }
+                    }
+                }
+                _ => violations.push(type_mismatch(self.schema_type, value, path)),
+            },
+            ASLANSchemaType::Object => {
+                let obj = match value.as_object() {
+                    Some(obj) => obj,
+                    None => return violations.push(type_mismatch(self.schema_type, value, path)),
+                };
+                for field in &self.fields {
+                    path.push(field.name.clone());
+                    match obj.get(&field.name) {
+                        Some(field_value) => field.schema.validate_at(field_value, default_field_name, path, violations),
+                        None if field.required => violations.push(violation(
+                            ASLANViolationKind::MissingField,
+                            path,
+                            &format!("required field `{}` is missing", field.name),
+                        )),
+                        None => {}
+                    }
+                    path.pop();
+                }
+                if !self.additional_fields {
+                    for key in obj.keys() {
+                        if key != default_field_name && self.get_field(key).is_none() {
+                            path.push(key.clone());
+                            violations.push(violation(
+                                ASLANViolationKind::UnknownField,
+                                path,
+                                &format!("field `{}` is not declared in the schema", key),
+                            ));
+                            path.pop();
+                        }
+                    }
+                }
+            }
+            ASLANSchemaType::Array => {
+                let arr = match value.as_array() {
+                    Some(arr) => arr,
+                    None => return violations.push(type_mismatch(self.schema_type, value, path)),
+                };
+                if let Some(min_items) = self.min_items {
+                    if arr.len() < min_items {
+                        violations.push(violation(
+                            ASLANViolationKind::TooFewItems,
+                            path,
+                            &format!("expected at least {} items but found {}", min_items, arr.len()),
+                        ));
+                    }
+                }
+                if let Some(max_items) = self.max_items {
+                    if arr.len() > max_items {
+                        violations.push(violation(
+                            ASLANViolationKind::TooManyItems,
+                            path,
+                            &format!("expected at most {} items but found {}", max_items, arr.len()),
+                        ));
+                    }
+                }
+                if let Some(items) = &self.items {
+                    for (i, item) in arr.iter().enumerate() {
+                        path.push(i.to_string());
+                        items.validate_at(item, default_field_name, path, violations);
+                        path.pop();
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_allowed_value(&self, s: &str, path: &[String], violations: &mut Vec<ASLANViolation>) {
+        if let Some(allowed) = &self.allowed_values {
+            // Surrounding whitespace is ignored, as it is when coercing leaves
+            if !allowed.iter().any(|a| a == s.trim()) {
+                violations.push(violation(
+                    ASLANViolationKind::DisallowedValue,
+                    path,
+                    &format!("value `{}` is not one of {:?}", s, allowed),
+                ));
+            }
+        }
+    }
+}
+
+impl ASLANSchema {
+    /// Build an open object schema from a map of dotted paths to leaf types.
+    /// A `*` segment stands for every item of an array, e.g. `items.*.price`.
+    pub fn from_type_map<I, S>(type_map: I) -> Self
+    where
+        I: IntoIterator<Item = (S, ASLANSchemaType)>,
+        S: AsRef<str>,
+    {
+        let mut root = Self::object();
+        for (path, schema_type) in type_map {
+            let segments: Vec<&str> = path.as_ref().split('.').collect();
+            root.insert_type_at(&segments, schema_type);
+        }
+        root
+    }
+
+    fn insert_type_at(&mut self, segments: &[&str], schema_type: ASLANSchemaType) {
+        let (segment, rest) = match segments.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+        let child_type = match rest.first() {
+            None => schema_type,
+            Some(&"*") => ASLANSchemaType::Array,
+            Some(_) => ASLANSchemaType::Object,
+        };
+        let child = if *segment == "*" {
+            if self.schema_type != ASLANSchemaType::Array {
+                return;
+            }
+            self.items.get_or_insert_with(|| Box::new(Self::scalar(child_type)))
+        } else {
+            if self.schema_type != ASLANSchemaType::Object {
+                return;
+            }
+            if self.get_field(segment).is_none() {
+                self.fields.push(ASLANSchemaField {
+                    name: segment.to_string(),
+                    schema: Self::scalar(child_type),
+                    required: false,
+                    duplicate_behavior: None,
+                    frequency: None,
+                });
+            }
+            &mut self.fields.iter_mut().find(|f| f.name == *segment).unwrap().schema
+        };
+        child.insert_type_at(rest, schema_type);
+    }
+
+    /// Trim and coerce typed leaves of `value` in place.
+    /// Leaves that fail keep their raw text and are reported as `CoercionFailed` violations.
+    pub fn coerce(&self, value: &mut Value) -> Vec<ASLANViolation> {
+        let mut violations = Vec::new();
+        let mut path = Vec::new();
+        self.coerce_at(value, &mut path, &mut violations);
+        violations
+    }
+
+    fn coerce_at(&self, value: &mut Value, path: &mut Vec<String>, violations: &mut Vec<ASLANViolation>) {
+        match (self.schema_type, value) {
+            (ASLANSchemaType::Object, Value::Object(obj)) => {
+                for field in &self.fields {
+                    if let Some(field_value) = obj.get_mut(&field.name) {
+                        path.push(field.name.clone());
+                        field.schema.coerce_at(field_value, path, violations);
+                        path.pop();
+                    }
+                }
+            }
+            (ASLANSchemaType::Array, Value::Array(arr)) => {
+                if let Some(items) = &self.items {
+                    for (i, item) in arr.iter_mut().enumerate() {
+                        path.push(i.to_string());
+                        items.coerce_at(item, path, violations);
+                        path.pop();
+                    }
+                }
+            }
+            (ASLANSchemaType::Integer | ASLANSchemaType::Number | ASLANSchemaType::Boolean, value) => {
+                if let Value::String(raw) = value {
+                    match self.schema_type.coerce(raw) {
+                        Some(coerced) => *value = coerced,
+                        None => violations.push(coercion_failed(self.schema_type, raw, path)),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ASLANSchema {
+    /// Generate an example ASLAN document with this shape using the given prefix
+    pub fn to_example(&self, prefix: &str) -> String {
+        let mut out = String::new();
+        if self.schema_type == ASLANSchemaType::Object {
+            self.write_example_fields(prefix, &mut out);
+        } else {
+            self.write_example_value(prefix, "value", &mut out);
+        }
+        out
+    }
+
+    fn write_example_fields(&self, prefix: &str, out: &mut String) {
+        for field in &self.fields {
+            out.push_str(&format!("[{}d_{}", prefix, field.name));
+            match field.duplicate_behavior {
+                Some(ASLANDuplicateKeyBehavior::Append) => out.push_str(":a"),
+                Some(ASLANDuplicateKeyBehavior::KeepFirst) => out.push_str(":f"),
+                Some(ASLANDuplicateKeyBehavior::KeepLast) => out.push_str(":l"),
+                None => {}
+            }
+            out.push(']');
+            field.schema.write_example_value(prefix, &field.name, out);
+        }
+    }
+
+    fn write_example_value(&self, prefix: &str, name: &str, out: &mut String) {
+        match self.schema_type {
+            ASLANSchemaType::Object => {
+                out.push_str(&format!("[{}o]\n", prefix));
+                self.write_example_fields(prefix, out);
+                out.push_str(&format!("[{}o]\n", prefix));
+            }
+            ASLANSchemaType::Array => {
+                out.push_str(&format!("[{}a]\n", prefix));
+                let count = match self.max_items {
+                    Some(max_items) => max_items.min(2),
+                    None => self.min_items.unwrap_or(0).max(2),
+                };
+                let items = self.items.as_deref().cloned().unwrap_or_default();
+                for i in 0..count {
+                    out.push_str(&format!("[{}d]", prefix));
+                    items.write_example_value(prefix, &format!("{} item {}", name, i + 1), out);
+                }
+                out.push_str(&format!("[{}a]\n", prefix));
+            }
+            ASLANSchemaType::Parts => {
+                for i in 0..2 {
+                    out.push_str(&format!("[{}p]", prefix));
+                    if let Some(instruction) = self.allowed_instructions.as_ref().and_then(|i| i.first()) {
+                        if i == 0 {
+                            out.push_str(&format!("[{}i_{}]", prefix, instruction));
+                        }
+                    }
+                    out.push_str(&format!("{} part {}", example_text(name), i + 1));
+                }
+                out.push('\n');
+            }
+            _ if self.escaped => {
+                out.push_str(&format!("[{}e_RAW]{}[{}e_RAW]\n", prefix, self.example_scalar(name), prefix));
+            }
+            _ => {
+                out.push_str(&self.example_scalar(name));
+                out.push('\n');
+            }
+        }
+    }
+
+    fn example_scalar(&self, name: &str) -> String {
+        if let Some(value) = self.allowed_values.as_ref().and_then(|v| v.first()) {
+            return value.clone();
+        }
+        match self.schema_type {
+            ASLANSchemaType::Integer => "42".to_string(),
+            ASLANSchemaType::Number => "3.14".to_string(),
+            ASLANSchemaType::Boolean => "true".to_string(),
+            _ => example_text(name),
+        }
+    }
+
+    /// Describe this shape as a human-readable, indented list of fields
+    pub fn describe_format(&self) -> String {
+        let mut out = String::new();
+        if self.schema_type == ASLANSchemaType::Object {
+            self.describe_fields(0, &mut out);
+        } else {
+            out.push_str(&format!("- {}\n", self.describe_type()));
+            self.describe_children(1, &mut out);
+        }
+        out
+    }
+
+    fn describe_fields(&self, depth: usize, out: &mut String) {
+        for field in &self.fields {
+            let mut notes = vec![if field.required { "required" } else { "optional" }.to_string()];
+            if let Some(frequency) = field.frequency.filter(|f| *f < 1.0) {
+                notes.push(format!("present in {:.0}% of results", frequency * 100.0));
+            }
+            match field.duplicate_behavior {
+                Some(ASLANDuplicateKeyBehavior::KeepFirst) => notes.push("if repeated, the first value is kept".to_string()),
+                Some(ASLANDuplicateKeyBehavior::KeepLast) => notes.push("if repeated, the last value is kept".to_string()),
+                Some(ASLANDuplicateKeyBehavior::Append) => notes.push("if repeated, values are appended".to_string()),
+                None => {}
+            }
+            out.push_str(&format!(
+                "{}- `{}`: {} ({})",
+                "  ".repeat(depth),
+                field.name,
+                field.schema.describe_type(),
+                notes.join(", ")
+            ));
+            if let Some(description) = &field.schema.description {
+                out.push_str(&format!(" - {}", description));
+            }
+            out.push('\n');
+            field.schema.describe_children(depth + 1, out);
+        }
+    }
+
+    fn describe_children(&self, depth: usize, out: &mut String) {
+        match self.schema_type {
+            ASLANSchemaType::Object => self.describe_fields(depth, out),
+            ASLANSchemaType::Array => {
+                if let Some(items) = &self.items {
+                    if matches!(items.schema_type, ASLANSchemaType::Object | ASLANSchemaType::Array) {
+                        out.push_str(&format!("{}- each item: {}\n", "  ".repeat(depth), items.describe_type()));
+                        items.describe_children(depth + 1, out);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn describe_type(&self) -> String {
+        let mut description = match self.schema_type {
+            ASLANSchemaType::String => "text".to_string(),
+            ASLANSchemaType::Parts => "text, optionally split into parts".to_string(),
+            ASLANSchemaType::Integer => "integer".to_string(),
+            ASLANSchemaType::Number => "number".to_string(),
+            ASLANSchemaType::Boolean => "`true` or `false`".to_string(),
+            ASLANSchemaType::Object => "object".to_string(),
+            ASLANSchemaType::Array => {
+                let items = self.items.as_deref().map(|i| i.describe_type()).unwrap_or_else(|| "text".to_string());
+                let mut description = format!("list of {}", items);
+                match (self.min_items, self.max_items) {
+                    (Some(min), Some(max)) => description.push_str(&format!(", {} to {} items", min, max)),
+                    (Some(min), None) => description.push_str(&format!(", at least {} items", min)),
+                    (None, Some(max)) => description.push_str(&format!(", at most {} items", max)),
+                    (None, None) => {}
+                }
+                description
+            }
+        };
+        if let Some(values) = &self.allowed_values {
+            let values: Vec<String> = values.iter().map(|v| format!("`{}`", v)).collect();
+            description.push_str(&format!(", one of {}", values.join(", ")));
+        }
+        if let Some(instructions) = &self.allowed_instructions {
+            let instructions: Vec<String> = instructions.iter().map(|i| format!("`{}`", i)).collect();
+            description.push_str(&format!(", instructions: {}", instructions.join(", ")));
+        }
+        if self.escaped {
+            description.push_str(", escaped");
+        }
+        if self.nullable {
+            description.push_str(", may be void");
+        }
+        description
+    }
+}
+
+/// Infer a schema from a corpus of parse results.
+///
+/// Every field records the share of observed objects containing it in `frequency` and is
+/// required only if it was always present. Values that were void at least once are nullable.
+/// A field holding plain strings in some results and arrays of strings in others is inferred
+/// as parts. Leaves are typed as integer, number or boolean when every observed value converts.
+/// Inferred objects accept additional fields.
+pub fn infer<I>(values: I) -> ASLANSchema
+where
+    I: IntoIterator,
+    I::Item: Borrow<Value>,
+{
+    infer_with_default_field(values, DEFAULT_FIELD_NAME)
+}
+
+/// Infer a schema from parse results produced with a renamed default field
+pub fn infer_with_default_field<I>(values: I, default_field_name: &str) -> ASLANSchema
+where
+    I: IntoIterator,
+    I::Item: Borrow<Value>,
+{
+    let mut root = Observations::default();
+    for value in values {
+        root.observe(value.borrow(), Some(default_field_name));
+    }
+    if root.seen == 0 {
+        return ASLANSchema::object();
+    }
+    root.to_schema()
+}
+
+/// Everything seen at one position of the results passed to `infer`
+#[derive(Default)]
+struct Observations {
+    seen: usize,
+    nulls: usize,
+    strings: usize,
+    integers: usize,
+    numbers: usize,
+    booleans: usize,
+    objects: usize,
+    arrays: usize,
+    /// Arrays holding only strings, which may be parts
+    string_arrays: usize,
+    fields: Vec<(String, Observations)>,
+    items: Option<Box<Observations>>,
+}
+
+impl Observations {
+    /// `default_field_name` is only set at the root, where the parser inserts the default field
+    fn observe(&mut self, value: &Value, default_field_name: Option<&str>) {
+        self.seen += 1;
+        match value {
+            Value::Null => self.nulls += 1,
+            Value::String(s) => {
+                self.strings += 1;
+                if ASLANSchemaType::Integer.coerce(s).is_some() {
+                    self.integers += 1;
+                }
+                if ASLANSchemaType::Number.coerce(s).is_some() {
+                    self.numbers += 1;
+                }
+                if ASLANSchemaType::Boolean.coerce(s).is_some() {
+                    self.booleans += 1;
+                }
+            }
+            // Already coerced leaves
+            Value::Bool(_) => {
+                self.strings += 1;
+                self.booleans += 1;
+            }
+            Value::Number(n) => {
+                self.strings += 1;
+                self.numbers += 1;
+                if n.is_i64() || n.is_u64() {
+                    self.integers += 1;
+                }
+            }
+            Value::Object(obj) => {
+                self.objects += 1;
+                for (key, field_value) in obj {
+                    if default_field_name == Some(key.as_str()) {
+                        continue;
+                    }
+                    let index = match self.fields.iter().position(|(name, _)| name == key) {
+                        Some(index) => index,
+                        None => {
+                            self.fields.push((key.clone(), Observations::default()));
+                            self.fields.len() - 1
+                        }
+                    };
+                    self.fields[index].1.observe(field_value, None);
+                }
+            }
+            Value::Array(arr) => {
+                self.arrays += 1;
+                if arr.iter().all(|item| item.is_string()) {
+                    self.string_arrays += 1;
+                }
+                let items = self.items.get_or_insert_with(Box::default);
+                for item in arr {
+                    items.observe(item, None);
+                }
+            }
+        }
+    }
+
+    fn to_schema(&self) -> ASLANSchema {
+        let is_parts = self.strings > 0 && self.arrays > 0 && self.string_arrays == self.arrays && self.objects == 0;
+        let mut schema = if is_parts {
+            ASLANSchema::parts()
+        } else if self.objects > 0 && self.objects >= self.arrays && self.objects >= self.strings {
+            let mut schema = ASLANSchema::object();
+            for (name, field) in &self.fields {
+                schema.fields.push(ASLANSchemaField {
+                    name: name.clone(),
+                    schema: field.to_schema(),
+                    required: field.seen >= self.objects,
+                    duplicate_behavior: None,
+                    frequency: Some(field.seen as f64 / self.objects as f64),
+                });
+            }
+            schema
+        } else if self.arrays > 0 && self.arrays >= self.strings {
+            let items = match &self.items {
+                Some(items) if items.seen > 0 => items.to_schema(),
+                _ => ASLANSchema::string(),
+            };
+            ASLANSchema::array(items)
+        } else if self.strings > 0 && self.booleans == self.strings {
+            ASLANSchema::scalar(ASLANSchemaType::Boolean)
+        } else if self.strings > 0 && self.integers == self.strings {
+            ASLANSchema::scalar(ASLANSchemaType::Integer)
+        } else if self.strings > 0 && self.numbers == self.strings {
+            ASLANSchema::scalar(ASLANSchemaType::Number)
+        } else {
+            ASLANSchema::string()
+        };
+        schema.nullable = self.nulls > 0;
+        schema
+    }
+}
+
+/// Types that describe their own ASLAN shape, usually implemented with `#[derive(AslanSchema)]`
+pub trait AslanSchema {
+    /// The schema describing this type
+    fn aslan_schema() -> ASLANSchema;
+
+    /// Whether a field of this type may be omitted
+    fn aslan_optional() -> bool {
+        false
+    }
+
+    /// An example ASLAN document for this type
+    fn aslan_example(prefix: &str) -> String {
+        Self::aslan_schema().to_example(prefix)
+    }
+
+    /// A human-readable description of this type's ASLAN format
+    fn aslan_format_description() -> String {
+        Self::aslan_schema().describe_format()
+    }
+}
+
+macro_rules! impl_aslan_schema_scalar {
+    ($schema_type:expr => $($t:ty),*) => {
+        $(
+            impl AslanSchema for $t {
+                fn aslan_schema() -> ASLANSchema {
+                    ASLANSchema::scalar($schema_type)
+                }
+            }
+        )*
+    };
+}
+
+impl_aslan_schema_scalar!(ASLANSchemaType::String => String, str, char);
+impl_aslan_schema_scalar!(ASLANSchemaType::Integer => i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_aslan_schema_scalar!(ASLANSchemaType::Number => f32, f64);
+impl_aslan_schema_scalar!(ASLANSchemaType::Boolean => bool);
+
+impl<T: AslanSchema + ?Sized> AslanSchema for &T {
+    fn aslan_schema() -> ASLANSchema {
+        T::aslan_schema()
+    }
+}
+
+impl<T: AslanSchema + ?Sized> AslanSchema for Box<T> {
+    fn aslan_schema() -> ASLANSchema {
+        T::aslan_schema()
+    }
+}
+
+impl<T: AslanSchema> AslanSchema for Option<T> {
+    fn aslan_schema() -> ASLANSchema {
+        T::aslan_schema().nullable()
+    }
+
+    fn aslan_optional() -> bool {
+        true
+    }
+}
+
+impl<T: AslanSchema> AslanSchema for Vec<T> {
+    fn aslan_schema() -> ASLANSchema {
+        ASLANSchema::array(T::aslan_schema())
+    }
+}
+
+fn example_text(name: &str) -> String {
+    format!("example {}", name.replace('_', " "))
+}
+
+fn schema_error(path: &[String], message: &str) -> ASLANSchemaError {
+    ASLANSchemaError {
+        path: path.to_vec(),
+        message: message.to_string(),
+    }
+}
+
+fn get_usize(value: Option<&Value>, path: &[String], keyword: &str) -> Result<Option<usize>, ASLANSchemaError> {
+    match value {
+        None => Ok(None),
+        Some(v) => v
+            .as_u64()
+            .map(|n| Some(n as usize))
+            .ok_or_else(|| schema_error(path, &format!("`{}` must be a non-negative integer", keyword))),
+    }
+}
+
+pub(crate) fn violation(kind: ASLANViolationKind, path: &[String], message: &str) -> ASLANViolation {
+    ASLANViolation {
+        kind,
+        path: path.to_vec(),
+        message: message.to_string(),
+    }
+}
+
+pub(crate) fn type_mismatch(expected: ASLANSchemaType, found: &Value, path: &[String]) -> ASLANViolation {
+    let found = match found {
+        Value::Null => "void",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    violation(
+        ASLANViolationKind::TypeMismatch,
+        path,
+        &format!("expected {} but found {}", expected.name(), found),
+    )
+}
+
+fn coercion_failed(expected: ASLANSchemaType, raw: &str, path: &[String]) -> ASLANViolation {
+    violation(
+        ASLANViolationKind::CoercionFailed,
+        path,
+        &format!("cannot coerce `{}` to {}", raw, expected.name()),
+    )
+}
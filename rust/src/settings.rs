@@ -0,0 +1,310 @@
+use crate::aslan_parser::{ASLANIndexUnit, ASLANParserSettings, ASLANTolerance, EmittableEvents};
+use crate::error::ASLANSettingsError;
+use crate::schema::ASLANSchema;
+use serde::{Deserialize, Serialize};
+
+/// Builds `ASLANParserSettings`, checking that the options are valid and work together.
+///
+/// Deserializing `ASLANParserSettings` goes through the builder, so settings loaded from
+/// config files are validated too. Missing options take their default value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ASLANParserSettingsBuilder {
+    prefix: String,
+    default_field_name: String,
+    strict_start: bool,
+    strict_end: bool,
+    emittable_events: EmittableEvents,
+    multi_aslan_output: bool,
+    collapse_object_start_whitespace: bool,
+    append_separator: String,
+    max_object_depth: Option<usize>,
+    schema: Option<ASLANSchema>,
+    coerce_leaves: bool,
+    track_spans: bool,
+    strict: bool,
+    additional_prefixes: Vec<String>,
+    detect_prefix: bool,
+    allowed_prefixes: Vec<String>,
+    tolerance: ASLANTolerance,
+    strip_code_fences: bool,
+    repair_indentation: bool,
+    instruction_index_unit: ASLANIndexUnit,
+    quoted_args: bool,
+    named_args: bool,
+    ranged_instructions: bool,
+}
+
+impl Default for ASLANParserSettingsBuilder {
+    fn default() -> Self {
+        ASLANParserSettings::default().into()
+    }
+}
+
+impl From<ASLANParserSettings> for ASLANParserSettingsBuilder {
+    fn from(settings: ASLANParserSettings) -> Self {
+        Self {
+            prefix: settings.prefix,
+            default_field_name: settings.default_field_name,
+            strict_start: settings.strict_start,
+            strict_end: settings.strict_end,
+            emittable_events: settings.emittable_events,
+            multi_aslan_output: settings.multi_aslan_output,
+            collapse_object_start_whitespace: settings.collapse_object_start_whitespace,
+            append_separator: settings.append_separator,
+            max_object_depth: settings.max_object_depth,
+            schema: settings.schema,
+            coerce_leaves: settings.coerce_leaves,
+            track_spans: settings.track_spans,
+            strict: settings.strict,
+            additional_prefixes: settings.additional_prefixes,
+            detect_prefix: settings.detect_prefix,
+            allowed_prefixes: settings.allowed_prefixes,
+            tolerance: settings.tolerance,
+            strip_code_fences: settings.strip_code_fences,
+            repair_indentation: settings.repair_indentation,
+            instruction_index_unit: settings.instruction_index_unit,
+            quoted_args: settings.quoted_args,
+            named_args: settings.named_args,
+            ranged_instructions: settings.ranged_instructions,
+        }
+    }
+}
+
+impl TryFrom<ASLANParserSettingsBuilder> for ASLANParserSettings {
+    type Error = ASLANSettingsError;
+
+    fn try_from(builder: ASLANParserSettingsBuilder) -> Result<Self, Self::Error> {
+        builder.build()
+    }
+}
+
+impl ASLANParserSettingsBuilder {
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn default_field_name(mut self, default_field_name: impl Into<String>) -> Self {
+        self.default_field_name = default_field_name.into();
+        self
+    }
+
+    pub fn strict_start(mut self, strict_start: bool) -> Self {
+        self.strict_start = strict_start;
+        self
+    }
+
+    pub fn strict_end(mut self, strict_end: bool) -> Self {
+        self.strict_end = strict_end;
+        self
+    }
+
+    pub fn emittable_events(mut self, emittable_events: EmittableEvents) -> Self {
+        self.emittable_events = emittable_events;
+        self
+    }
+
+    pub fn multi_aslan_output(mut self, multi_aslan_output: bool) -> Self {
+        self.multi_aslan_output = multi_aslan_output;
+        self
+    }
+
+    pub fn collapse_object_start_whitespace(mut self, collapse_object_start_whitespace: bool) -> Self {
+        self.collapse_object_start_whitespace = collapse_object_start_whitespace;
+        self
+    }
+
+    pub fn append_separator(mut self, append_separator: impl Into<String>) -> Self {
+        self.append_separator = append_separator.into();
+        self
+    }
+
+    pub fn max_object_depth(mut self, max_object_depth: Option<usize>) -> Self {
+        self.max_object_depth = max_object_depth;
+        self
+    }
+
+    pub fn schema(mut self, schema: ASLANSchema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    pub fn coerce_leaves(mut self, coerce_leaves: bool) -> Self {
+        self.coerce_leaves = coerce_leaves;
+        self
+    }
+
+    pub fn track_spans(mut self, track_spans: bool) -> Self {
+        self.track_spans = track_spans;
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn additional_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.additional_prefixes.push(prefix.into());
+        self
+    }
+
+    pub fn detect_prefix(mut self, detect_prefix: bool) -> Self {
+        self.detect_prefix = detect_prefix;
+        self
+    }
+
+    pub fn allowed_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.allowed_prefixes.push(prefix.into());
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: ASLANTolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn strip_code_fences(mut self, strip_code_fences: bool) -> Self {
+        self.strip_code_fences = strip_code_fences;
+        self
+    }
+
+    pub fn repair_indentation(mut self, repair_indentation: bool) -> Self {
+        self.repair_indentation = repair_indentation;
+        self
+    }
+
+    pub fn instruction_index_unit(mut self, instruction_index_unit: ASLANIndexUnit) -> Self {
+        self.instruction_index_unit = instruction_index_unit;
+        self
+    }
+
+    pub fn quoted_args(mut self, quoted_args: bool) -> Self {
+        self.quoted_args = quoted_args;
+        self
+    }
+
+    pub fn named_args(mut self, named_args: bool) -> Self {
+        self.named_args = named_args;
+        self
+    }
+
+    pub fn ranged_instructions(mut self, ranged_instructions: bool) -> Self {
+        self.ranged_instructions = ranged_instructions;
+        self
+    }
+
+    /// Validate the options and build the settings
+    pub fn build(self) -> Result<ASLANParserSettings, ASLANSettingsError> {
+        let settings = ASLANParserSettings {
+            prefix: self.prefix,
+            default_field_name: self.default_field_name,
+            strict_start: self.strict_start,
+            strict_end: self.strict_end,
+            emittable_events: self.emittable_events,
+            multi_aslan_output: self.multi_aslan_output,
+            collapse_object_start_whitespace: self.collapse_object_start_whitespace,
+            append_separator: self.append_separator,
+            max_object_depth: self.max_object_depth,
+            schema: self.schema,
+            coerce_leaves: self.coerce_leaves,
+            track_spans: self.track_spans,
+            strict: self.strict,
+            additional_prefixes: self.additional_prefixes,
+            detect_prefix: self.detect_prefix,
+            allowed_prefixes: self.allowed_prefixes,
+            tolerance: self.tolerance,
+            strip_code_fences: self.strip_code_fences,
+            repair_indentation: self.repair_indentation,
+            instruction_index_unit: self.instruction_index_unit,
+            quoted_args: self.quoted_args,
+            named_args: self.named_args,
+            ranged_instructions: self.ranged_instructions,
+        };
+        settings.validate()?;
+        Ok(settings)
+    }
+}
+
+impl ASLANParserSettings {
+    /// Start building settings from the defaults
+    pub fn builder() -> ASLANParserSettingsBuilder {
+        ASLANParserSettingsBuilder::default()
+    }
+
+    /// Check that the options are valid and work together
+    pub fn validate(&self) -> Result<(), ASLANSettingsError> {
+        // Spec: prefixes are alphanumeric
+        let prefixes: Vec<&String> = std::iter::once(&self.prefix).chain(&self.additional_prefixes).collect();
+        for (i, prefix) in prefixes.iter().enumerate() {
+            let field = if i == 0 { "prefix" } else { "additional_prefixes" };
+            if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(ASLANSettingsError::new(
+                    field,
+                    &format!("`{}` must be a non-empty string of ASCII letters and digits", prefix),
+                ));
+            }
+            let overlapping = prefixes
+                .iter()
+                .enumerate()
+                .find(|(j, other)| *j != i && prefix.starts_with(other.as_str()));
+            if let Some((_, other)) = overlapping {
+                return Err(ASLANSettingsError::new(
+                    "additional_prefixes",
+                    &format!("`{}` starts with `{}`, so their delimiters cannot be told apart", prefix, other),
+                ));
+            }
+        }
+        for prefix in &self.allowed_prefixes {
+            if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(ASLANSettingsError::new(
+                    "allowed_prefixes",
+                    &format!("`{}` must be a non-empty string of ASCII letters and digits", prefix),
+                ));
+            }
+        }
+        if !self.allowed_prefixes.is_empty() && !self.detect_prefix {
+            return Err(ASLANSettingsError::new("allowed_prefixes", "requires detect_prefix"));
+        }
+        if self.detect_prefix && !self.additional_prefixes.is_empty() {
+            return Err(ASLANSettingsError::new(
+                "additional_prefixes",
+                "cannot be combined with detect_prefix, which locks onto a single prefix",
+            ));
+        }
+        if self.default_field_name.is_empty()
+            || !self.default_field_name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(ASLANSettingsError::new(
+                "default_field_name",
+                &format!(
+                    "`{}` must be a non-empty string of ASCII letters, digits and underscores",
+                    self.default_field_name
+                ),
+            ));
+        }
+        match self.max_object_depth {
+            Some(0) => {
+                return Err(ASLANSettingsError::new(
+                    "max_object_depth",
+                    "must be at least 1, as no object could be opened otherwise",
+                ))
+            }
+            Some(_) if !self.collapse_object_start_whitespace => {
+                return Err(ASLANSettingsError::new(
+                    "max_object_depth",
+                    "requires collapse_object_start_whitespace, otherwise whitespace before an opening delimiter closes the object instead",
+                ))
+            }
+            _ => {}
+        }
+        if self.coerce_leaves && self.schema.is_none() {
+            return Err(ASLANSettingsError::new(
+                "coerce_leaves",
+                "requires a schema describing the leaf types",
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use aslan::{ASLANParser, ASLANParserSettings};
+use serde_json::json;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn fence_stripping_parser() -> ASLANParser {
+    ASLANParser::with_settings(ASLANParserSettings::builder().strip_code_fences(true).build().unwrap())
+}
+
+#[test]
+fn keeps_fences_by_default() {
+    let mut parser = ASLANParser::new();
+    let result = parser.parse("```aslan\n[asland_a]1\n```");
+    assert_eq!(result, json!({ "_default": "```aslan\n", "a": "1\n```" }));
+}
+
+#[test]
+fn strips_fences_around_the_payload() {
+    for input in [
+        "```aslan\n[asland_a]1[asland_b]2\n```",
+        "```\n[asland_a]1[asland_b]2\n```\n",
+        "\n  ```aslan \r\n[asland_a]1[asland_b]2\n  ````  \n",
+    ] {
+        let mut parser = fence_stripping_parser();
+        assert_eq!(parser.parse(input), json!({ "_default": null, "a": "1", "b": "2" }), "{:?}", input);
+    }
+}
+
+#[test]
+fn keeps_fences_inside_field_content_and_escapes() {
+    let mut parser = fence_stripping_parser();
+    let input = "```aslan\n[asland_code]\n```rust\nfn main() {}\n```\n[asland_raw][aslane_T]\n```\n[aslane_T]\n```";
+    assert_eq!(parser.parse(input), json!({
+        "_default": null,
+        "code": "\n```rust\nfn main() {}\n```\n",
+        "raw": "\n```\n",
+    }));
+
+    let mut parser = fence_stripping_parser();
+    assert_eq!(parser.parse("```\n[asland_raw][aslane_T]x\n```")["raw"], json!("x\n```"));
+}
+
+#[test]
+fn strips_a_fence_after_text_before_the_first_delimiter() {
+    let mut parser = fence_stripping_parser();
+    assert_eq!(parser.parse("Here:\n```aslan\n[asland_x]a\n```"), json!({ "_default": "Here:\n", "x": "a" }));
+
+    let mut parser = fence_stripping_parser();
+    let result = parser.parse("[asland_x]a\n```aslan\nb\n```");
+    assert_eq!(result, json!({ "_default": null, "x": "a\n```aslan\nb\n```" }));
+}
+
+#[test]
+fn keeps_a_trailing_fence_without_an_opening_one() {
+    let mut parser = fence_stripping_parser();
+    assert_eq!(parser.parse("[asland_a]1\n```"), json!({ "_default": null, "a": "1\n```" }));
+}
+
+#[test]
+fn strips_fences_split_across_chunks() {
+    let input = "```aslan\n[asland_a]x\ny\n``\n```\n";
+    let mut whole = fence_stripping_parser();
+    let expected = whole.parse(input);
+    assert_eq!(expected, json!({ "_default": null, "a": "x\ny\n``" }));
+    for split in 0..input.len() {
+        let mut parser = fence_stripping_parser();
+        parser.parse_next(&input[..split]);
+        parser.parse_next(&input[split..]);
+        parser.close();
+        assert_eq!(parser.get_result(), expected, "split at {}", split);
+    }
+}
+
+#[test]
+fn keeps_offsets_of_the_original_input() {
+    let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+        strip_code_fences: true,
+        track_spans: true,
+        ..Default::default()
+    });
+    let input = "```aslan\n[asland_a]1\n```";
+    parser.parse(input);
+    let spans = parser.spans().into_iter().find(|spans| spans.path == vec!["a"]).unwrap();
+    let content = spans.content[0];
+    assert_eq!(&input[content.start..content.end], "1");
+}
+
+#[test]
+fn closes_once_with_buffered_fence_input() {
+    let mut parser = fence_stripping_parser();
+    let ends = Rc::new(RefCell::new(0));
+    let ends_clone = ends.clone();
+    parser.add_end_data_listener(move |_| *ends_clone.borrow_mut() += 1);
+    parser.parse_next("```aslan\n[asland_a][aslano][asland_b]x\n");
+    parser.close();
+    let ends_after_first_close = *ends.borrow();
+    parser.close();
+    assert_eq!(*ends.borrow(), ends_after_first_close);
+    assert_eq!(parser.get_result(), json!({ "_default": null, "a": { "b": "x\n" } }));
+}
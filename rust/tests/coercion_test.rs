@@ -0,0 +1,111 @@
+use aslan::{ASLANParser, ASLANParserSettings, ASLANSchema, ASLANSchemaType, ASLANViolationKind};
+use serde_json::json;
+
+fn coercing_parser(schema: ASLANSchema) -> ASLANParser {
+    ASLANParser::with_settings(ASLANParserSettings {
+        schema: Some(schema),
+        coerce_leaves: true,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn coerces_typed_leaves_on_close() {
+    let schema = ASLANSchema::object()
+        .with_field("count", ASLANSchema::scalar(ASLANSchemaType::Integer))
+        .with_field("ratio", ASLANSchema::scalar(ASLANSchemaType::Number))
+        .with_field("done", ASLANSchema::scalar(ASLANSchemaType::Boolean))
+        .with_field("name", ASLANSchema::string());
+    let mut parser = coercing_parser(schema);
+    let result = parser.parse("[asland_count]42\n[asland_ratio] 0.5 [asland_done]True \n[asland_name] Bob \n");
+    assert_eq!(result, json!({
+        "_default": null,
+        "count": 42,
+        "ratio": 0.5,
+        "done": true,
+        "name": " Bob \n"
+    }));
+    assert!(parser.get_violations().is_empty());
+}
+
+#[test]
+fn keeps_raw_text_and_reports_failed_coercions() {
+    let schema = ASLANSchema::object().with_field("prices", ASLANSchema::array(ASLANSchema::scalar(ASLANSchemaType::Number)));
+    let mut parser = coercing_parser(schema);
+    let result = parser.parse("[asland_prices][aslana][asland]1.25[asland]about 3[asland]7");
+    assert_eq!(result, json!({
+        "_default": null,
+        "prices": [1.25, "about 3", 7]
+    }));
+    let violations = parser.get_violations();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].kind, ASLANViolationKind::CoercionFailed);
+    assert_eq!(violations[0].path, vec!["prices".to_string(), "1".to_string()]);
+    assert_eq!(violations[0].message, "cannot coerce `about 3` to number");
+}
+
+#[test]
+fn keeps_appending_to_the_raw_text_after_close() {
+    let mut parser = coercing_parser(ASLANSchema::object().with_field("n", ASLANSchema::scalar(ASLANSchemaType::Number)));
+    parser.parse_next("[asland_n]12");
+    parser.close();
+    assert_eq!(parser.get_result()["n"], json!(12));
+    parser.parse_next("34");
+    assert_eq!(parser.get_result()["n"], json!("1234"));
+    parser.close();
+    assert_eq!(parser.get_result()["n"], json!(1234));
+}
+
+#[test]
+fn leaves_strings_untouched_without_coerce_leaves() {
+    let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+        schema: Some(ASLANSchema::object().with_field("count", ASLANSchema::scalar(ASLANSchemaType::Integer))),
+        ..Default::default()
+    });
+    let result = parser.parse("[asland_count]42\n");
+    assert_eq!(result["count"], "42\n");
+    // A string that would coerce is still valid for an integer field
+    assert!(parser.get_violations().is_empty());
+}
+
+#[test]
+fn coerces_with_per_path_type_map() {
+    let schema = ASLANSchema::from_type_map([
+        ("order.total", ASLANSchemaType::Number),
+        ("order.items.*.qty", ASLANSchemaType::Integer),
+        ("paid", ASLANSchemaType::Boolean),
+    ]);
+    let mut parser = coercing_parser(schema);
+    let result = parser.parse(
+        "[asland_order][aslano][asland_total]9.5[asland_items][aslana][asland][aslano][asland_qty]2\n[asland_sku]A1[aslano][aslana][aslano][asland_paid]false[asland_note]kept",
+    );
+    assert_eq!(result, json!({
+        "_default": null,
+        "order": {
+            "total": 9.5,
+            "items": [{ "qty": 2, "sku": "A1" }]
+        },
+        "paid": false,
+        "note": "kept"
+    }));
+}
+
+#[test]
+fn loads_typed_leaves_from_json_schema() {
+    let schema = ASLANSchema::from_json_schema(&json!({
+        "properties": {
+            "age": { "type": "integer" },
+            "ok": { "type": ["boolean", "null"] }
+        }
+    }))
+    .unwrap();
+    assert_eq!(schema.get_field("age").unwrap().schema.schema_type, ASLANSchemaType::Integer);
+    assert_eq!(schema.get_field("ok").unwrap().schema.schema_type, ASLANSchemaType::Boolean);
+
+    let mut parser = coercing_parser(schema);
+    let result = parser.parse("[asland_age]x[asland_ok][aslanv]");
+    assert_eq!(result["age"], "x");
+    assert_eq!(result["ok"], json!(null));
+    let kinds: Vec<ASLANViolationKind> = parser.get_violations().iter().map(|v| v.kind).collect();
+    assert_eq!(kinds, vec![ASLANViolationKind::CoercionFailed]);
+}
@@ -14,3 +14,27 @@ fn parses_simple_string_with_parts_and_delimiter_like_items() {
         ]
     }));
 }
+
+#[test]
+fn parses_delimiter_directly_after_broken_delimiter() {
+    let mut parser = ASLANParser::new();
+    let result = parser.parse("[asland_a][x[asland_b]see [asl[asland_c]arr[[asland_d]done");
+    assert_eq!(result, json!({
+        "_default": null,
+        "a": "[x",
+        "b": "see [asl",
+        "c": "arr[",
+        "d": "done"
+    }));
+}
+
+#[test]
+fn keeps_broken_delimiter_followed_by_delimiter() {
+    let mut parser = ASLANParser::new();
+    let result = parser.parse("[asland_a]1[asl][asland_b]2");
+    assert_eq!(result, json!({
+        "_default": null,
+        "a": "1[asl]",
+        "b": "2"
+    }));
+}
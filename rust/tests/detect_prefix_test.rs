@@ -0,0 +1,75 @@
+use aslan::{ASLANDiagnosticCode, ASLANParser, ASLANParserSettings};
+use serde_json::json;
+
+fn detecting_parser() -> ASLANParser {
+    ASLANParser::with_settings(ASLANParserSettings::builder().detect_prefix(true).build().unwrap())
+}
+
+#[test]
+fn locks_onto_the_prefix_of_the_first_data_delimiter() {
+    let mut parser = detecting_parser();
+    assert_eq!(parser.detected_prefix(), None);
+    let result = parser.parse("Sure! [llmd_title]Pancakes[llmd_tags][llma][llmd]sweet[llma][llmd_note][asland_x]y");
+    assert_eq!(parser.detected_prefix(), Some("llm"));
+    assert_eq!(result, json!({
+        "_default": "Sure! ",
+        "title": "Pancakes",
+        "tags": ["sweet"],
+        "note": "[asland_x]y",
+    }));
+}
+
+#[test]
+fn skips_brackets_that_do_not_form_a_delimiter() {
+    let mut parser = detecting_parser();
+    let result = parser.parse("[todo] [1] [x] [food_ bar] [asl[asland_a][aslano]");
+    assert_eq!(parser.detected_prefix(), Some("aslan"));
+    assert_eq!(result["_default"], json!("[todo] [1] [x] [food_ bar] [asl"));
+    assert_eq!(result["a"], json!({}));
+    assert!(parser.diagnostics().iter().all(|d| d.code != ASLANDiagnosticCode::MalformedDelimiter));
+}
+
+#[test]
+fn detects_prefixes_split_across_chunks() {
+    let input = "Hi [llmd_a]1[llmd_b]2";
+    for split in 0..input.len() {
+        let mut parser = detecting_parser();
+        parser.parse_next(&input[..split]);
+        parser.parse_next(&input[split..]);
+        parser.close();
+        assert_eq!(parser.get_result(), json!({ "_default": "Hi ", "a": "1", "b": "2" }), "split at {}", split);
+        assert_eq!(parser.detected_prefix(), Some("llm"));
+    }
+}
+
+#[test]
+fn only_detects_allowed_prefixes() {
+    let settings = ASLANParserSettings::builder()
+        .detect_prefix(true)
+        .allowed_prefix("aslan")
+        .allowed_prefix("llm")
+        .build()
+        .unwrap();
+    let mut parser = ASLANParser::with_settings(settings);
+    let result = parser.parse("[notd_a]1[llmo][llmd_b]2");
+    assert_eq!(parser.detected_prefix(), Some("llm"));
+    assert_eq!(result, json!({ "_default": "[notd_a]1", "b": "2" }));
+}
+
+#[test]
+fn detects_the_go_delimiter_with_strict_start() {
+    let settings = ASLANParserSettings::builder().detect_prefix(true).strict_start(true).build().unwrap();
+    let mut parser = ASLANParser::with_settings(settings);
+    parser.parse("[bugd_x]ignored [llmg][llmd_a]1[llms]");
+    assert_eq!(parser.detected_prefix(), Some("llm"));
+    assert_eq!(parser.get_results().last().unwrap()["a"], json!("1"));
+}
+
+#[test]
+fn rejects_invalid_detection_settings() {
+    let error = ASLANParserSettings::builder().allowed_prefix("llm").build().unwrap_err();
+    assert_eq!(error.to_string(), "invalid setting `allowed_prefixes`: requires detect_prefix");
+    assert!(ASLANParserSettings::builder().detect_prefix(true).allowed_prefix("l m").build().is_err());
+    let error = ASLANParserSettings::builder().detect_prefix(true).additional_prefix("llm").build().unwrap_err();
+    assert_eq!(error.field, "additional_prefixes");
+}
@@ -0,0 +1,104 @@
+use aslan::{ASLANDiagnostic, ASLANDiagnosticCode, ASLANDiagnosticSeverity, ASLANParser};
+use serde_json::json;
+
+fn diagnose(input: &str) -> Vec<ASLANDiagnostic> {
+    let mut parser = ASLANParser::new();
+    parser.parse(input);
+    parser.diagnostics()
+}
+
+fn codes(diagnostics: &[ASLANDiagnostic]) -> Vec<ASLANDiagnosticCode> {
+    diagnostics.iter().map(|d| d.code).collect()
+}
+
+#[test]
+fn reports_nothing_for_well_formed_input() {
+    let diagnostics = diagnose("[asland_a]1[asland_b][aslano][asland_c][aslanv]\n[aslano][asland_d][aslana][asland]x[aslana]");
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+}
+
+#[test]
+fn reports_invalid_array_index_at_its_delimiter() {
+    let input = "[asland_list][aslana][asland]a[asland_x]b[aslana]";
+    let mut parser = ASLANParser::new();
+    assert_eq!(parser.parse(input)["list"], json!(["a", "b"]));
+    let diagnostics = parser.diagnostics();
+    assert_eq!(codes(&diagnostics), vec![ASLANDiagnosticCode::InvalidArrayIndex]);
+    assert_eq!(diagnostics[0].offset, input.find("[asland_x]").unwrap());
+    assert_eq!(diagnostics[0].path, vec!["list", "1"]);
+    assert_eq!(diagnostics[0].severity, ASLANDiagnosticSeverity::Warning);
+}
+
+#[test]
+fn reports_duplicate_voids_and_content_after_void_once() {
+    let input = "[asland_a][aslanv][aslanv]dropped text\n[asland_b][aslanv]\n";
+    let mut parser = ASLANParser::new();
+    assert_eq!(parser.parse(input), json!({ "_default": null, "a": null, "b": null }));
+    let diagnostics = parser.diagnostics();
+    assert_eq!(codes(&diagnostics), vec![ASLANDiagnosticCode::DuplicateVoid, ASLANDiagnosticCode::ContentAfterVoid]);
+    assert_eq!(diagnostics[0].offset, 18);
+    assert_eq!(diagnostics[1].offset, input.find("dropped").unwrap());
+    assert_eq!(diagnostics[1].path, vec!["a"]);
+}
+
+#[test]
+fn reports_unterminated_escape_at_the_end() {
+    let input = "[asland_a][aslano][asland_b][aslane_T]x [asland_c]";
+    let diagnostics = diagnose(input);
+    assert_eq!(codes(&diagnostics), vec![ASLANDiagnosticCode::UnterminatedEscape]);
+    assert_eq!(diagnostics[0].offset, input.len());
+    assert_eq!(diagnostics[0].severity, ASLANDiagnosticSeverity::Error);
+}
+
+#[test]
+fn does_not_report_blocks_closed_at_the_end_of_input() {
+    // Spec: Blocks left open at the end of the stream are closed, explicit closes are optional
+    assert!(diagnose("[asland_a][aslano][asland_b][aslana][asland]1").is_empty());
+}
+
+#[test]
+fn reports_end_of_input_diagnostics_once_when_closed_twice() {
+    let mut parser = ASLANParser::new();
+    parser.parse_next("[asland_a][aslano][asland_b][aslane_T]1");
+    parser.close();
+    parser.close();
+    assert_eq!(codes(&parser.diagnostics()), vec![ASLANDiagnosticCode::UnterminatedEscape]);
+
+    parser.parse_next("2");
+    parser.close();
+    assert_eq!(parser.diagnostics().len(), 2);
+    assert_eq!(parser.get_result()["a"], json!({ "b": "12" }));
+}
+
+#[test]
+fn reports_extraneous_closes_at_root() {
+    let input = "[asland_a]1[aslano][asland_b]2";
+    let mut parser = ASLANParser::new();
+    assert_eq!(parser.parse(input), json!({ "_default": null, "a": "1", "b": "2" }));
+    let diagnostics = parser.diagnostics();
+    assert_eq!(codes(&diagnostics), vec![ASLANDiagnosticCode::ExtraneousClose]);
+    assert_eq!(diagnostics[0].offset, 11);
+}
+
+#[test]
+fn reports_reserved_and_malformed_delimiters() {
+    let input = "[asland_a]x[aslanq]y[aslan d_b]";
+    let mut parser = ASLANParser::new();
+    assert_eq!(parser.parse(input)["a"], json!("xy[aslan d_b]"));
+    let diagnostics = parser.diagnostics();
+    assert_eq!(codes(&diagnostics), vec![ASLANDiagnosticCode::ReservedDelimiter, ASLANDiagnosticCode::MalformedDelimiter]);
+    assert_eq!(diagnostics[0].offset, 11);
+    assert_eq!(diagnostics[0].message, "`[aslanq]` has a reserved suffix and is dropped");
+    assert_eq!(diagnostics[1].offset, 20);
+}
+
+#[test]
+fn reports_ignored_duplicate_behavior() {
+    let input = "[asland_a:f]1[asland_a:l]2[asland_a:f]3[asland_a]4";
+    let mut parser = ASLANParser::new();
+    assert_eq!(parser.parse(input)["a"], json!("1"));
+    let diagnostics = parser.diagnostics();
+    assert_eq!(codes(&diagnostics), vec![ASLANDiagnosticCode::ConflictingDuplicateBehavior]);
+    assert_eq!(diagnostics[0].offset, 13);
+    assert_eq!(diagnostics[0].path, vec!["a"]);
+}
@@ -0,0 +1,127 @@
+use aslan::{
+    ASLANDelimiterExtension, ASLANExtensionContent, ASLANExtensionContext, ASLANExtensionDelimiter, ASLANExtensionGrammar,
+    ASLANParser, ASLANTokenKind,
+};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// `[aslant_integer]` converts the current field
+struct TypedValues;
+
+impl ASLANDelimiterExtension for TypedValues {
+    fn suffix(&self) -> char {
+        't'
+    }
+
+    fn grammar(&self) -> ASLANExtensionGrammar {
+        ASLANExtensionGrammar {
+            content: ASLANExtensionContent::Required,
+            args: false,
+        }
+    }
+
+    fn handle(&mut self, delimiter: &ASLANExtensionDelimiter, context: &mut ASLANExtensionContext) {
+        let text = context.value.as_ref().and_then(Value::as_str).unwrap_or_default().trim().to_string();
+        context.value = match delimiter.content.as_deref() {
+            Some("integer") => text.parse::<i64>().ok().map(Value::from),
+            Some("boolean") => text.parse::<bool>().ok().map(Value::from),
+            _ => context.value.take(),
+        };
+    }
+}
+
+/// `[aslanr:doc:3]` emits a reference event
+#[derive(Default)]
+struct References {
+    seen: usize,
+}
+
+impl ASLANDelimiterExtension for References {
+    fn suffix(&self) -> char {
+        'r'
+    }
+
+    fn grammar(&self) -> ASLANExtensionGrammar {
+        ASLANExtensionGrammar {
+            content: ASLANExtensionContent::Optional,
+            args: true,
+        }
+    }
+
+    fn handle(&mut self, delimiter: &ASLANExtensionDelimiter, context: &mut ASLANExtensionContext) {
+        self.seen += 1;
+        context.emit(json!({ "n": self.seen, "kind": delimiter.content, "args": delimiter.args }));
+    }
+}
+
+#[test]
+fn drops_unregistered_suffixes_as_reserved() {
+    let mut parser = ASLANParser::new();
+    assert_eq!(parser.parse("[asland_age]42[aslant]"), json!({ "_default": null, "age": "42" }));
+}
+
+#[test]
+fn lets_handlers_change_the_current_field() {
+    let mut parser = ASLANParser::new();
+    parser.register_extension(TypedValues).unwrap();
+    let result = parser.parse("[asland_age] 42 [aslant_integer][asland_vegan]true[aslant_boolean][asland_name]Ann[aslant_integer]");
+    assert_eq!(result, json!({ "_default": null, "age": 42, "vegan": true, "name": "Ann" }));
+}
+
+#[test]
+fn emits_custom_events_with_the_field_path() {
+    let mut parser = ASLANParser::new();
+    parser.register_extension(References::default()).unwrap();
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let events_clone = events.clone();
+    parser.add_extension_listener(move |event| events_clone.borrow_mut().push(event.clone()));
+
+    let result = parser.parse("[asland_a][aslano][asland_b]see [aslanr_doc:intro:3]and [aslanr][aslano]");
+    assert_eq!(result, json!({ "_default": null, "a": { "b": "see and " } }));
+
+    let events = events.borrow();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].suffix, 'r');
+    assert_eq!(events[0].path, vec!["a", "b"]);
+    assert_eq!(events[0].data, json!({ "n": 1, "kind": "doc", "args": ["intro", "3"] }));
+    assert_eq!(events[1].data, json!({ "n": 2, "kind": null, "args": [] }));
+}
+
+#[test]
+fn keeps_delimiters_breaking_the_grammar_as_text() {
+    let mut parser = ASLANParser::new();
+    parser.register_extension(TypedValues).unwrap();
+    let result = parser.parse("[asland_a]1[aslant][asland_b]2[aslant:x][asland_c]3[aslant_int_]");
+    assert_eq!(result, json!({ "_default": null, "a": "1[aslant]", "b": "2[aslant:x]", "c": "3[aslant_int_]" }));
+}
+
+#[test]
+fn lexes_delimiters_of_registered_extensions() {
+    let mut parser = ASLANParser::new();
+    parser.register_extension(TypedValues).unwrap();
+    let tokens = parser.lex("[asland_age]42[aslant_integer]");
+    assert_eq!(tokens[2].kind, ASLANTokenKind::ExtensionDelimiter {
+        prefix: "aslan".to_string(),
+        suffix: 't',
+        content: Some("integer".to_string()),
+        args: Vec::new(),
+    });
+}
+
+#[test]
+fn rejects_spec_and_duplicate_suffixes() {
+    struct Data;
+    impl ASLANDelimiterExtension for Data {
+        fn suffix(&self) -> char {
+            'd'
+        }
+        fn handle(&mut self, _: &ASLANExtensionDelimiter, _: &mut ASLANExtensionContext) {}
+    }
+
+    let mut parser = ASLANParser::new();
+    assert!(parser.register_extension(Data).is_err());
+    parser.register_extension(TypedValues).unwrap();
+    let error = parser.register_extension(TypedValues).unwrap_err();
+    assert_eq!(error.to_string(), "invalid setting `extensions`: an extension for `t` is already registered");
+}
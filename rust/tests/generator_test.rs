@@ -0,0 +1,116 @@
+use aslan::generator::{ASLANGenerator, ASLANGeneratorSettings};
+use aslan::{ASLANDuplicateKeyBehavior, ASLANParser, ASLANParserSettings, ASLANSchema, ASLANSchemaField, ASLANSchemaType};
+use serde_json::{json, Value};
+
+fn order_schema() -> ASLANSchema {
+    let mut schema = ASLANSchema::object()
+        .with_field("customer", ASLANSchema::object().with_field("name", ASLANSchema::string()).with_optional_field("email", ASLANSchema::string().nullable()))
+        .with_field("lines", ASLANSchema::array(
+            ASLANSchema::object()
+                .with_field("sku", ASLANSchema::string())
+                .with_field("qty", ASLANSchema::scalar(ASLANSchemaType::Integer))
+                .with_optional_field("tags", ASLANSchema::array(ASLANSchema::string().nullable())),
+        ))
+        .with_field("notes", ASLANSchema::parts().with_instructions(&["bold", "italic"]))
+        .with_field("status", ASLANSchema::string().with_allowed_values(&["open", "shipped"]))
+        .with_field("snippet", ASLANSchema::string().escaped())
+        .with_optional_field("grid", ASLANSchema::array(ASLANSchema::array(ASLANSchema::scalar(ASLANSchemaType::Number))));
+    schema.fields.push(ASLANSchemaField {
+        name: "summary".to_string(),
+        schema: ASLANSchema::string(),
+        required: true,
+        duplicate_behavior: Some(ASLANDuplicateKeyBehavior::KeepLast),
+        frequency: None,
+    });
+    schema
+}
+
+fn parse_chunks(chunks: &[String], prefix: &str) -> Value {
+    let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+        prefix: prefix.to_string(),
+        ..Default::default()
+    });
+    for chunk in chunks {
+        parser.parse_next(chunk);
+    }
+    parser.close();
+    parser.get_result()
+}
+
+#[test]
+fn generated_documents_parse_to_expected_result() {
+    let schema = order_schema();
+    for seed in 0..300 {
+        let document = ASLANGenerator::new(seed).generate(&schema);
+        assert_eq!(document.chunks.concat(), document.text);
+        assert_eq!(parse_chunks(&document.chunks, "aslan"), document.expected, "seed {}: {:?}", seed, document.text);
+    }
+}
+
+#[test]
+fn generates_from_sample_value() {
+    let sample = json!({
+        "title": "Launch [asland_plan] notes",
+        "count": 3,
+        "ok": true,
+        "empty": "",
+        "missing": null,
+        "items": [{ "id": "a1" }, "loose", ["x", "y"]],
+        "meta": { "author": { "name": "Ada" } }
+    });
+    let expected = json!({
+        "_default": null,
+        "title": "Launch [asland_plan] notes",
+        "count": "3",
+        "ok": "true",
+        "empty": null,
+        "missing": null,
+        "items": [{ "id": "a1" }, "loose", ["x", "y"]],
+        "meta": { "author": { "name": "Ada" } }
+    });
+    let settings = ASLANGeneratorSettings {
+        preamble: false,
+        ..Default::default()
+    };
+    for seed in 0..100 {
+        let document = ASLANGenerator::with_settings(seed, settings.clone()).generate_from_value(&sample);
+        assert_eq!(document.expected, expected);
+        assert_eq!(parse_chunks(&document.chunks, "aslan"), expected, "seed {}: {:?}", seed, document.text);
+    }
+}
+
+#[test]
+fn is_deterministic_per_seed() {
+    let schema = order_schema();
+    assert_eq!(ASLANGenerator::new(42).generate(&schema), ASLANGenerator::new(42).generate(&schema));
+    assert_ne!(ASLANGenerator::new(1).generate(&schema).text, ASLANGenerator::new(2).generate(&schema).text);
+}
+
+#[test]
+fn includes_each_kind_of_noise() {
+    let schema = order_schema();
+    let texts: Vec<String> = (0..100).map(|seed| ASLANGenerator::new(seed).generate(&schema).text).collect();
+    for needle in ["Sure!", "[aslanc]", "[aslanv][aslanv]", "[asland_summary:l]", ":f]", "[aslane_RAW]", "[Your name]", "[aslanp][aslani_", "[aslano]\n"] {
+        assert!(texts.iter().any(|t| t.contains(needle)), "no document contains {:?}", needle);
+    }
+}
+
+#[test]
+fn uses_custom_prefix_without_noise() {
+    let settings = ASLANGeneratorSettings {
+        prefix: "out".to_string(),
+        preamble: false,
+        comments: false,
+        whitespace: false,
+        duplicate_keys: false,
+        voids: false,
+        delimiter_like_noise: false,
+        max_chunk_len: 1,
+    };
+    let schema = ASLANSchema::object().with_field("a", ASLANSchema::string()).with_field("b", ASLANSchema::array(ASLANSchema::string()));
+    let document = ASLANGenerator::with_settings(3, settings).generate(&schema);
+    assert!(document.text.starts_with("[outd_a]"));
+    assert!(!document.text.contains("[aslan"));
+    assert!(document.chunks.iter().all(|c| c.chars().count() == 1));
+    assert_eq!(parse_chunks(&document.chunks, "out"), document.expected);
+}
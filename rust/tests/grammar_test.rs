@@ -0,0 +1,99 @@
+use aslan::grammar::{to_gbnf, to_regex};
+use aslan::{ASLANParser, ASLANParserSettings, ASLANSchema, ASLANSchemaType};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use regex::Regex;
+
+fn article_schema() -> ASLANSchema {
+    ASLANSchema::object()
+        .with_field("title", ASLANSchema::string())
+        .with_field("status", ASLANSchema::string().with_allowed_values(&["draft", "final"]))
+        .with_field("words", ASLANSchema::scalar(ASLANSchemaType::Integer))
+        .with_field(
+            "sections",
+            ASLANSchema::array(
+                ASLANSchema::object()
+                    .with_field("heading", ASLANSchema::string())
+                    .with_field("body", ASLANSchema::parts().with_instructions(&["bold"]))
+                    .closed(),
+            )
+            .with_item_bounds(Some(1), Some(3)),
+        )
+        .with_optional_field("note", ASLANSchema::string().nullable())
+        .with_field("code", ASLANSchema::string().escaped())
+        .closed()
+}
+
+fn full_match(pattern: &str) -> Regex {
+    Regex::new(&format!("^(?:{})$", pattern)).unwrap()
+}
+
+#[test]
+fn exports_gbnf_rules() {
+    let schema = ASLANSchema::object()
+        .with_field("answer", ASLANSchema::string())
+        .with_optional_field("ok", ASLANSchema::scalar(ASLANSchemaType::Boolean).nullable())
+        .closed();
+    assert_eq!(
+        to_gbnf(&schema, "ab"),
+        "root ::= \"[abd_answer]\" answer (\"[abd_ok]\" ok)?\n\
+         answer ::= text\n\
+         ok ::= ws (\"true\" | \"false\") ws | \"[abv]\" ws\n\
+         ws ::= [ \\t\\r\\n]*\n\
+         chunk ::= [^\\[] | \"[\" ([^a\\[] | \"a\" [^b\\[])\n\
+         text ::= chunk+\n"
+    );
+}
+
+#[test]
+fn gbnf_renames_colliding_rules() {
+    let schema = ASLANSchema::object()
+        .with_field("text", ASLANSchema::string())
+        .with_field("a-b", ASLANSchema::array(ASLANSchema::string()))
+        .with_field("a_b", ASLANSchema::string());
+    let gbnf = to_gbnf(&schema, "aslan");
+    assert!(gbnf.contains("root ::= \"[asland_text]\" text-2 \"[asland_a-b]\" a-b \"[asland_a_b]\" a-b-2 (\"[asland_\" key \"]\" text)*\n"));
+    assert!(gbnf.contains("a-b ::= \"[aslana]\" ws (\"[asland]\" a-b-item)* \"[aslana]\" ws\n"));
+    assert!(gbnf.contains("key ::= [a-zA-Z0-9_]+\n"));
+}
+
+#[test]
+fn regex_accepts_schema_example() {
+    let schema = article_schema();
+    let regex = full_match(&to_regex(&schema, "aslan"));
+    assert!(regex.is_match(&schema.to_example("aslan")));
+}
+
+#[test]
+fn regex_rejects_structure_outside_schema() {
+    let regex = full_match(&to_regex(&article_schema(), "aslan"));
+    for sample in [
+        // Unknown field in a closed object
+        "[asland_title]x[asland_extra]y",
+        // Text that would be read as a delimiter
+        "[asland_title]see [asland_status]draft[asland_words]1[asland_sections][aslana][asland][aslano][asland_heading]h[asland_body]b[aslano][aslana][asland_code][aslane_RAW]c[aslane_RAW]\n[asland_oops]",
+        // Too many array items
+        "[asland_title]t[asland_status]final[asland_words]3[asland_sections][aslana][asland][aslano][asland_heading]h[asland_body]b[aslano][asland][aslano][asland_heading]h[asland_body]b[aslano][asland][aslano][asland_heading]h[asland_body]b[aslano][asland][aslano][asland_heading]h[asland_body]b[aslano][aslana][asland_code][aslane_RAW]c[aslane_RAW]",
+        // Disallowed instruction
+        "[asland_title]t[asland_status]draft[asland_words]3[asland_sections][aslana][asland][aslano][asland_heading]h[asland_body][aslanp][aslani_italic]b[aslano][aslana][asland_code][aslane_RAW]c[aslane_RAW]",
+    ] {
+        assert!(!regex.is_match(sample), "unexpected match: {}", sample);
+    }
+}
+
+#[test]
+fn grammar_accepted_samples_parse_to_the_schema() {
+    let schema = article_schema();
+    let pattern = to_regex(&schema, "aslan");
+    let generator = rand_regex::Regex::compile(&pattern, 4).unwrap();
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..200 {
+        let sample: String = rng.sample(&generator);
+        let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+            schema: Some(schema.clone()),
+            ..Default::default()
+        });
+        parser.parse(&sample);
+        assert_eq!(parser.get_violations(), vec![], "sample: {:?}", sample);
+    }
+}
@@ -0,0 +1,71 @@
+use aslan::{ASLANDiagnosticCode, ASLANParser, ASLANParserSettings};
+use serde_json::json;
+
+fn repairing_parser() -> ASLANParser {
+    ASLANParser::with_settings(ASLANParserSettings::builder().repair_indentation(true).build().unwrap())
+}
+
+fn inferred_closes(parser: &ASLANParser) -> Vec<(Vec<String>, String)> {
+    parser
+        .diagnostics()
+        .into_iter()
+        .filter(|diagnostic| diagnostic.code == ASLANDiagnosticCode::InferredClose)
+        .map(|diagnostic| (diagnostic.path, diagnostic.message))
+        .collect()
+}
+
+#[test]
+fn matches_explicitly_closed_input() {
+    let closed = "[asland_author][aslano]\n  [asland_name]Ann\n  [asland_links][aslana]\n    [asland]a\n    [asland]b\n[aslana][aslano]\n[asland_title]Pancakes";
+    let unclosed = "[asland_author][aslano]\n  [asland_name]Ann\n  [asland_links][aslana]\n    [asland]a\n    [asland]b\n[asland_title]Pancakes";
+    let expected = ASLANParser::new().parse(closed);
+    assert_eq!(expected["title"], json!("Pancakes"));
+
+    let mut parser = repairing_parser();
+    assert_eq!(parser.parse(unclosed), expected);
+    assert_eq!(inferred_closes(&parser), vec![
+        (vec!["author".to_string(), "links".to_string()], "array is closed by the indentation of the next field".to_string()),
+        (vec!["author".to_string()], "object is closed by the indentation of the next field".to_string()),
+    ]);
+
+    // Without the repair, the title is nested in the last open block
+    assert_eq!(ASLANParser::new().parse(unclosed)["author"]["links"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn closes_only_the_blocks_indented_out_of() {
+    let mut parser = repairing_parser();
+    let result = parser.parse("[asland_a][aslano]\n  [asland_b][aslano]\n    [asland_c]1\n  [asland_d]2\n[asland_e]3");
+    assert_eq!(result, json!({
+        "_default": null,
+        "a": { "b": { "c": "1\n  " }, "d": "2\n" },
+        "e": "3",
+    }));
+}
+
+#[test]
+fn ignores_blocks_without_indented_fields() {
+    let input = "[asland_a][aslano]\n[asland_b]1\n[aslano]\n[asland_c][aslano][asland_d]2[aslano]\n[asland_e]3";
+    let mut parser = repairing_parser();
+    assert_eq!(parser.parse(input), ASLANParser::new().parse(input));
+    assert!(inferred_closes(&parser).is_empty());
+}
+
+#[test]
+fn ignores_explicit_closes_and_fields_later_on_a_line() {
+    let input = "[asland_a][aslano]\n  [asland_b]1[asland_c]2\n[aslano]\n[asland_d]3";
+    let mut parser = repairing_parser();
+    assert_eq!(parser.parse(input), ASLANParser::new().parse(input));
+    assert!(inferred_closes(&parser).is_empty());
+}
+
+#[test]
+fn repairs_streamed_input() {
+    let input = "[asland_a][aslano]\n\t[asland_b]1\n[asland_c]2";
+    let mut parser = repairing_parser();
+    for chunk in input.as_bytes().chunks(3) {
+        parser.parse_next(std::str::from_utf8(chunk).unwrap());
+    }
+    parser.close();
+    assert_eq!(parser.get_result(), json!({ "_default": null, "a": { "b": "1\n" }, "c": "2" }));
+}
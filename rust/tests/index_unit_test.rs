@@ -0,0 +1,70 @@
+use aslan::{ASLANEndDataInstruction, ASLANIndexUnit, ASLANInstruction, ASLANParser, ASLANParserSettings};
+use serde_json::json;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Accented letter, emoji with skin tone modifier, flag and a combining accent
+const TEXT: &str = "[asland_body]Cr\u{e8}me \u{1F44D}\u{1F3FD} \u{1F1EB}\u{1F1F7} e\u{301}[aslani_bold]!";
+
+fn indices(unit: ASLANIndexUnit) -> (Vec<usize>, Vec<usize>) {
+    let settings = ASLANParserSettings::builder().instruction_index_unit(unit).build().unwrap();
+    let mut parser = ASLANParser::with_settings(settings);
+    let content: Rc<RefCell<Vec<ASLANInstruction>>> = Rc::new(RefCell::new(Vec::new()));
+    let end_data: Rc<RefCell<Vec<ASLANEndDataInstruction>>> = Rc::new(RefCell::new(Vec::new()));
+
+    let content_clone = content.clone();
+    parser.add_content_listener(move |event| content_clone.borrow_mut().push(event.clone()));
+    let end_data_clone = end_data.clone();
+    parser.add_end_data_listener(move |event| end_data_clone.borrow_mut().push(event.clone()));
+
+    let result = parser.parse(TEXT);
+    assert_eq!(result["body"], json!("Cr\u{e8}me \u{1F44D}\u{1F3FD} \u{1F1EB}\u{1F1F7} e\u{301}!"));
+
+    let content_indices = content.borrow().iter().map(|event| event.index).collect();
+    let end_data_indices = end_data
+        .borrow()
+        .iter()
+        .flat_map(|event| event.content.iter().flat_map(|part| part.instructions.iter().map(|i| i.index)))
+        .collect();
+    (content_indices, end_data_indices)
+}
+
+#[test]
+fn defaults_to_utf8_bytes() {
+    assert_eq!(indices(ASLANIndexUnit::default()), (vec![28, 28], vec![28]));
+}
+
+#[test]
+fn counts_unicode_scalars() {
+    assert_eq!(indices(ASLANIndexUnit::Chars), (vec![14, 14], vec![14]));
+}
+
+#[test]
+fn counts_utf16_code_units() {
+    assert_eq!(indices(ASLANIndexUnit::Utf16), (vec![18, 18], vec![18]));
+}
+
+#[test]
+fn counts_grapheme_clusters() {
+    assert_eq!(indices(ASLANIndexUnit::Graphemes), (vec![11, 11], vec![11]));
+}
+
+#[test]
+fn counts_within_the_current_part() {
+    let settings = ASLANParserSettings::builder().instruction_index_unit(ASLANIndexUnit::Utf16).build().unwrap();
+    let mut parser = ASLANParser::with_settings(settings);
+    let content: Rc<RefCell<Vec<ASLANInstruction>>> = Rc::new(RefCell::new(Vec::new()));
+    let content_clone = content.clone();
+    parser.add_content_listener(move |event| content_clone.borrow_mut().push(event.clone()));
+
+    parser.parse("[asland_body][aslanp]\u{1F600}\u{1F600}[aslanp]\u{e9}[aslani_bold]x");
+    let content = content.borrow();
+    assert_eq!(content[0].index, 1);
+    assert_eq!(content[0].part_index, 1);
+}
+
+#[test]
+fn index_unit_round_trips_through_serde() {
+    let settings: ASLANParserSettings = serde_json::from_value(json!({ "instruction_index_unit": "Utf16" })).unwrap();
+    assert_eq!(settings.instruction_index_unit, ASLANIndexUnit::Utf16);
+}
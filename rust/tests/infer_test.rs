@@ -0,0 +1,115 @@
+use aslan::schema::{infer, infer_with_default_field};
+use aslan::{ASLANParser, ASLANSchemaType};
+use serde_json::json;
+use std::process::Command;
+
+#[test]
+fn infers_presence_frequencies_and_nullability() {
+    let results = [
+        json!({ "_default": null, "title": "A", "note": "x" }),
+        json!({ "_default": "Sure!", "title": "B", "note": null }),
+        json!({ "_default": null, "title": "C" }),
+        json!({ "_default": null, "title": "D" }),
+    ];
+    let schema = infer(&results);
+    assert_eq!(schema.schema_type, ASLANSchemaType::Object);
+    assert!(schema.additional_fields);
+    assert!(schema.get_field("_default").is_none());
+
+    let title = schema.get_field("title").unwrap();
+    assert!(title.required);
+    assert_eq!(title.frequency, Some(1.0));
+    assert!(!title.schema.nullable);
+
+    let note = schema.get_field("note").unwrap();
+    assert!(!note.required);
+    assert_eq!(note.frequency, Some(0.5));
+    assert!(note.schema.nullable);
+    assert_eq!(note.schema.schema_type, ASLANSchemaType::String);
+}
+
+#[test]
+fn tells_parts_from_arrays() {
+    let outputs = [
+        "[asland_body]Plain[asland_tags][aslana][asland]a[asland]b[aslana][asland_rows][aslana][asland][aslano][asland_n]1[aslano][aslana]",
+        "[asland_body][aslanp]One[aslanp]Two[asland_tags][aslana][asland]c[aslana][asland_rows][aslana][asland][aslano][asland_n]2\n[asland_m]x[aslano][aslana]",
+    ];
+    let results: Vec<_> = outputs.iter().map(|output| ASLANParser::new().parse(output)).collect();
+    let schema = infer(results);
+
+    assert_eq!(schema.get_field("body").unwrap().schema.schema_type, ASLANSchemaType::Parts);
+
+    let tags = &schema.get_field("tags").unwrap().schema;
+    assert_eq!(tags.schema_type, ASLANSchemaType::Array);
+    assert_eq!(tags.items.as_ref().unwrap().schema_type, ASLANSchemaType::String);
+
+    let row = schema.get_field("rows").unwrap().schema.items.as_deref().unwrap().clone();
+    assert_eq!(row.schema_type, ASLANSchemaType::Object);
+    assert_eq!(row.get_field("n").unwrap().schema.schema_type, ASLANSchemaType::Integer);
+    assert_eq!(row.get_field("m").unwrap().frequency, Some(0.5));
+}
+
+#[test]
+fn infers_typed_leaves_only_when_every_value_converts() {
+    let schema = infer([
+        json!({ "count": "3\n", "price": "2.5", "ok": "true", "code": "7" }),
+        json!({ "count": 4, "price": "3", "ok": false, "code": "7a" }),
+    ]);
+    let type_of = |name: &str| schema.get_field(name).unwrap().schema.schema_type;
+    assert_eq!(type_of("count"), ASLANSchemaType::Integer);
+    assert_eq!(type_of("price"), ASLANSchemaType::Number);
+    assert_eq!(type_of("ok"), ASLANSchemaType::Boolean);
+    assert_eq!(type_of("code"), ASLANSchemaType::String);
+}
+
+#[test]
+fn describes_inferred_frequencies() {
+    let schema = infer([json!({ "a": "x", "b": "y" }), json!({ "a": "x" }), json!({ "a": "z" })]);
+    assert_eq!(
+        schema.describe_format(),
+        "- `a`: text (required)\n- `b`: text (optional, present in 33% of results)\n"
+    );
+}
+
+#[test]
+fn skips_a_renamed_default_field_at_the_root() {
+    let results = [json!({ "preamble": "Sure!", "a": { "preamble": "x" } }), json!({ "preamble": null, "a": { "preamble": "y" } })];
+    let schema = infer_with_default_field(&results, "preamble");
+    assert!(schema.get_field("preamble").is_none());
+    assert!(schema.get_field("a").unwrap().schema.get_field("preamble").is_some());
+    assert!(infer(&results).get_field("preamble").is_some());
+}
+
+#[test]
+fn cli_infers_schema_from_json_lines() {
+    let path = std::env::temp_dir().join(format!("aslan_infer_test_{}.jsonl", std::process::id()));
+    std::fs::write(&path, "{\"_default\":null,\"a\":\"x\",\"b\":[\"1\"]}\n\n{\"_default\":null,\"a\":\"y\"}\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aslan"))
+        .args(["infer", "--text", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "- `a`: text (required)\n- `b`: list of integer (optional, present in 50% of results)\n"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aslan")).args(["infer", path.to_str().unwrap()]).output().unwrap();
+    let schema: aslan::ASLANSchema = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(schema.get_field("b").unwrap().frequency, Some(0.5));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aslan"))
+        .args(["infer", "--text", "--default-field", "a", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "- `_default`: text, may be void (required)\n- `b`: list of integer (optional, present in 50% of results)\n"
+    );
+    std::fs::remove_file(&path).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aslan")).args(["infer", "/nonexistent/results.jsonl"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().starts_with("aslan: cannot read /nonexistent/results.jsonl"));
+}
@@ -37,11 +37,11 @@ fn parses_simple_string_with_instructions() {
 
     // Verify we got content events
     let content = content_events.borrow();
-    assert!(content.len() > 0, "Should have content events");
+    assert!(!content.is_empty(), "Should have content events");
 
     // Verify we got end events
     let end = end_events.borrow();
-    assert!(end.len() > 0, "Should have end events");
+    assert!(!end.is_empty(), "Should have end events");
 
     // Check first content event instruction
     let first_event = &content[0];
@@ -0,0 +1,148 @@
+use aslan::generator::ASLANGenerator;
+use aslan::{
+    lex, syntax_tree, ASLANBlockEdge, ASLANDelimiterType, ASLANParser, ASLANParserSettings, ASLANSchema,
+    ASLANSchemaType, ASLANSyntaxNode, ASLANTokenKind, ASLANTolerance,
+};
+
+fn kinds(text: &str) -> Vec<(ASLANTokenKind, String)> {
+    lex(text, &ASLANParserSettings::default()).into_iter().map(|token| (token.kind, token.text)).collect()
+}
+
+fn delimiter(delimiter_type: ASLANDelimiterType, content: Option<&str>, args: &[&str]) -> ASLANTokenKind {
+    ASLANTokenKind::Delimiter {
+        prefix: "aslan".to_string(),
+        delimiter_type,
+        content: content.map(|c| c.to_string()),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        block: None,
+    }
+}
+
+#[test]
+fn lexes_text_and_delimiters_with_content_and_args() {
+    let tokens = kinds("Hi [asland_title:a]Pancakes[aslanp][aslani_link:x:y]now[asland_n][aslanv]");
+    assert_eq!(tokens, vec![
+        (ASLANTokenKind::Text, "Hi ".to_string()),
+        (delimiter(ASLANDelimiterType::Data, Some("title"), &["a"]), "[asland_title:a]".to_string()),
+        (ASLANTokenKind::Text, "Pancakes".to_string()),
+        (delimiter(ASLANDelimiterType::Part, None, &[]), "[aslanp]".to_string()),
+        (delimiter(ASLANDelimiterType::Instruction, Some("link"), &["x", "y"]), "[aslani_link:x:y]".to_string()),
+        (ASLANTokenKind::Text, "now".to_string()),
+        (delimiter(ASLANDelimiterType::Data, Some("n"), &[]), "[asland_n]".to_string()),
+        (delimiter(ASLANDelimiterType::Void, None, &[]), "[aslanv]".to_string()),
+    ]);
+}
+
+#[test]
+fn lexes_malformed_and_reserved_delimiters() {
+    let tokens = kinds("[aslan d_x] [1] [aslanq][asland_a b][asl[asland_ok]v[asland_");
+    assert_eq!(tokens, vec![
+        (ASLANTokenKind::MalformedDelimiter, "[aslan".to_string()),
+        (ASLANTokenKind::Text, " d_x] [1] ".to_string()),
+        (ASLANTokenKind::ReservedDelimiter { prefix: "aslan".to_string(), suffix: 'q' }, "[aslanq]".to_string()),
+        (ASLANTokenKind::MalformedDelimiter, "[asland_a".to_string()),
+        (ASLANTokenKind::Text, " b][asl".to_string()),
+        (delimiter(ASLANDelimiterType::Data, Some("ok"), &[]), "[asland_ok]".to_string()),
+        (ASLANTokenKind::Text, "v".to_string()),
+        (ASLANTokenKind::MalformedDelimiter, "[asland_".to_string()),
+    ]);
+}
+
+#[test]
+fn lexes_escape_and_comment_bodies() {
+    let tokens = kinds("[aslane_T]a [asland_x] b[aslane_T][aslanc]note[asland_y]z");
+    assert_eq!(tokens, vec![
+        (delimiter(ASLANDelimiterType::Escape, Some("T"), &[]), "[aslane_T]".to_string()),
+        (ASLANTokenKind::EscapeBody, "a [asland_x] b".to_string()),
+        (delimiter(ASLANDelimiterType::Escape, Some("T"), &[]), "[aslane_T]".to_string()),
+        (delimiter(ASLANDelimiterType::Comment, None, &[]), "[aslanc]".to_string()),
+        (ASLANTokenKind::CommentBody, "note".to_string()),
+        (delimiter(ASLANDelimiterType::Data, Some("y"), &[]), "[asland_y]".to_string()),
+        (ASLANTokenKind::Text, "z".to_string()),
+    ]);
+}
+
+#[test]
+fn marks_whether_blocks_open_or_close() {
+    let edges: Vec<Option<ASLANBlockEdge>> = lex("[asland_a][aslano][asland_b][aslana][asland]x[aslana][aslano][aslano]", &ASLANParserSettings::default())
+        .into_iter()
+        .filter_map(|token| match token.kind {
+            ASLANTokenKind::Delimiter { block, .. } if block.is_some() => Some(block),
+            _ => None,
+        })
+        .collect();
+    use ASLANBlockEdge::{Close, Open};
+    assert_eq!(edges, vec![Some(Open), Some(Open), Some(Close), Some(Close), Some(Close)]);
+}
+
+#[test]
+fn lexes_with_the_settings_of_the_parser() {
+    let text = "[ASLANd_a]1[aslani_b:\"x:y\"]";
+    let tokens = lex(text, &ASLANParserSettings::default());
+    assert_eq!(tokens[0].kind, ASLANTokenKind::Text);
+    assert_eq!(tokens[0].text, "[ASLANd_a]1");
+    assert_eq!(tokens[1].kind, delimiter(ASLANDelimiterType::Instruction, Some("b"), &["\"x", "y\""]));
+
+    let settings = ASLANParserSettings::builder().tolerance(ASLANTolerance::all()).quoted_args(true).build().unwrap();
+    let tokens = lex(text, &settings);
+    assert_eq!(tokens[0].kind, delimiter(ASLANDelimiterType::Data, Some("a"), &[]));
+    assert_eq!(tokens[2].kind, delimiter(ASLANDelimiterType::Instruction, Some("b"), &["x:y"]));
+}
+
+#[test]
+fn builds_syntax_tree_of_fields_and_blocks() {
+    let text = "intro[asland_a]1[asland_b][aslano]\n  [asland_c]2\n[aslano][asland_d]3";
+    let tree = syntax_tree(text, &ASLANParserSettings::default());
+    assert_eq!(tree.text(), text);
+    assert_eq!(tree.children.len(), 4);
+    assert!(matches!(&tree.children[0], ASLANSyntaxNode::Token(token) if token.text == "intro"));
+
+    let b = match &tree.children[2] {
+        ASLANSyntaxNode::Field(children) => children,
+        other => panic!("expected field, got {:?}", other),
+    };
+    let block = match &b[1] {
+        ASLANSyntaxNode::Block(children) => children,
+        other => panic!("expected block, got {:?}", other),
+    };
+    assert!(matches!(&block[0], ASLANSyntaxNode::Token(token) if token.text == "[aslano]"));
+    assert!(matches!(&block[1], ASLANSyntaxNode::Token(token) if token.text == "\n  "));
+    assert!(matches!(&block[2], ASLANSyntaxNode::Field(children) if children.len() == 2));
+    assert!(matches!(&block[3], ASLANSyntaxNode::Token(token) if token.text == "[aslano]"));
+}
+
+#[test]
+fn round_trips_generated_documents_and_agrees_with_the_parser() {
+    let schema = ASLANSchema::object()
+        .with_field("title", ASLANSchema::string())
+        .with_field("body", ASLANSchema::parts().with_instructions(&["bold"]))
+        .with_field("author", ASLANSchema::object().with_field("name", ASLANSchema::string()).with_optional_field("email", ASLANSchema::string().nullable()))
+        .with_field("rows", ASLANSchema::array(ASLANSchema::array(ASLANSchema::scalar(ASLANSchemaType::Integer))))
+        .with_field("snippet", ASLANSchema::string().escaped());
+    for seed in 0..200 {
+        let document = ASLANGenerator::new(seed).generate(&schema);
+        let tokens = lex(&document.text, &ASLANParserSettings::default());
+        assert_eq!(tokens.iter().map(|t| t.text.as_str()).collect::<String>(), document.text, "seed {}", seed);
+        for pair in tokens.windows(2) {
+            assert_eq!(pair[0].span.end, pair[1].span.start);
+        }
+        assert_eq!(syntax_tree(&document.text, &ASLANParserSettings::default()).text(), document.text);
+
+        // Every delimiter the parser attributes to a value is a delimiter token
+        let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+            track_spans: true,
+            ..Default::default()
+        });
+        parser.parse(&document.text);
+        for spans in parser.spans() {
+            for span in &spans.delimiters {
+                assert!(
+                    tokens.iter().any(|token| token.span == *span && matches!(token.kind, ASLANTokenKind::Delimiter { .. })),
+                    "seed {}: {:?}",
+                    seed,
+                    &document.text[span.start..span.end]
+                );
+            }
+        }
+    }
+}
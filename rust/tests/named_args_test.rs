@@ -0,0 +1,90 @@
+use aslan::{ASLANEndDataInstruction, ASLANInstruction, ASLANParser, ASLANParserSettings};
+use serde_json::json;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+fn named_parser() -> ASLANParser {
+    ASLANParser::with_settings(ASLANParserSettings::builder().named_args(true).build().unwrap())
+}
+
+fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+fn content_events(parser: &mut ASLANParser, input: &str) -> Vec<ASLANInstruction> {
+    let events: Rc<RefCell<Vec<ASLANInstruction>>> = Rc::new(RefCell::new(Vec::new()));
+    let events_clone = events.clone();
+    parser.add_content_listener(move |event| events_clone.borrow_mut().push(event.clone()));
+    parser.parse(input);
+    let events = events.borrow().clone();
+    events
+}
+
+fn end_data_events(parser: &mut ASLANParser, input: &str) -> Vec<ASLANEndDataInstruction> {
+    let events: Rc<RefCell<Vec<ASLANEndDataInstruction>>> = Rc::new(RefCell::new(Vec::new()));
+    let events_clone = events.clone();
+    parser.add_end_data_listener(move |event| events_clone.borrow_mut().push(event.clone()));
+    parser.parse(input);
+    let events = events.borrow().clone();
+    events
+}
+
+#[test]
+fn reads_named_instruction_args_alongside_positional_ones() {
+    let mut parser = named_parser();
+    let events = content_events(&mut parser, "[asland_body]A cat[aslani_image:alt=Cat:width=300:inline]");
+    assert_eq!(events[0].args, vec!["alt=Cat", "width=300", "inline"]);
+    assert_eq!(events[0].named_args, map(&[("alt", "Cat"), ("width", "300")]));
+}
+
+#[test]
+fn leaves_named_args_empty_by_default() {
+    let mut parser = ASLANParser::new();
+    let events = content_events(&mut parser, "[asland_body]A cat[aslani_image:alt=Cat]");
+    assert_eq!(events[0].args, vec!["alt=Cat"]);
+    assert!(events[0].named_args.is_empty());
+}
+
+#[test]
+fn ignores_args_without_a_valid_name() {
+    let mut parser = named_parser();
+    let events = content_events(&mut parser, "[asland_body]x[aslani_style:=1:a-b=2:color=:size=a=b]");
+    assert_eq!(events[0].named_args, map(&[("color", ""), ("size", "a=b")]));
+}
+
+#[test]
+fn reports_named_args_in_end_data_instructions() {
+    let mut parser = named_parser();
+    let events = end_data_events(&mut parser, "[asland_body][aslanp]One[aslani_link:href=a][aslanp]Two[aslani_link:href=b]");
+    let body = events.iter().find(|event| event.field_name == "body").unwrap();
+    let hrefs: Vec<_> = body
+        .content
+        .iter()
+        .map(|part| part.instructions[0].named_args["href"].clone())
+        .collect();
+    assert_eq!(hrefs, vec!["a", "b"]);
+}
+
+#[test]
+fn annotates_data_fields_with_named_args() {
+    let mut parser = named_parser();
+    let events = end_data_events(&mut parser, "[asland_title:lang=en:source=web]Hi[asland_note:a:by=me]x[asland_note:a:by=you]y");
+    assert_eq!(parser.get_result(), json!({ "_default": null, "title": "Hi", "note": "xy" }));
+
+    let title = events.iter().find(|event| event.field_name == "title").unwrap();
+    assert_eq!(title.annotations, map(&[("lang", "en"), ("source", "web")]));
+
+    let annotations = parser.annotations();
+    assert_eq!(annotations.len(), 2);
+    assert_eq!(annotations[1].path, vec!["note"]);
+    assert_eq!(annotations[1].named_args, map(&[("by", "you")]));
+}
+
+#[test]
+fn reads_named_args_in_quotes() {
+    let settings = ASLANParserSettings::builder().named_args(true).quoted_args(true).build().unwrap();
+    let mut parser = ASLANParser::with_settings(settings);
+    let events = content_events(&mut parser, "[asland_body]x[aslani_link:\"href=https://example.com/?q=1\"]");
+    assert_eq!(events[0].named_args, map(&[("href", "https://example.com/?q=1")]));
+}
@@ -0,0 +1,107 @@
+use aslan::{lex, ASLANParser, ASLANParserSettings, ASLANPrefixRouter, ASLANTokenKind, ASLANTolerance};
+use serde_json::json;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[test]
+fn merges_additional_prefixes_into_one_result() {
+    let mut parser = ASLANParser::with_settings(
+        ASLANParserSettings::builder().additional_prefix("llm").build().unwrap(),
+    );
+    let result = parser.parse("[asland_a]1[llmd_b][llmo][asland_c]2[aslano][llmd_d]3");
+    assert_eq!(result, json!({ "_default": null, "a": "1", "b": { "c": "2" }, "d": "3" }));
+}
+
+#[test]
+fn keeps_other_prefixes_as_text_by_default() {
+    let mut parser = ASLANParser::new();
+    assert_eq!(parser.parse("[asland_a]1[llmd_b]2")["a"], json!("1[llmd_b]2"));
+}
+
+#[test]
+fn rejects_prefixes_that_cannot_be_told_apart() {
+    let error = ASLANParserSettings::builder().additional_prefix("aslanx").build().unwrap_err();
+    assert_eq!(error.field, "additional_prefixes");
+    let error = ASLANParserSettings::builder().prefix("llmx").additional_prefix("llm").build().unwrap_err();
+    assert_eq!(error.to_string(), "invalid setting `additional_prefixes`: `llmx` starts with `llm`, so their delimiters cannot be told apart");
+    assert!(ASLANParserSettings::builder().additional_prefix("l-m").build().is_err());
+}
+
+#[test]
+fn lexes_the_prefix_of_each_delimiter() {
+    let prefixes: Vec<String> = lex("[asland_a][llmd_b][llmq]", &ASLANParserSettings::builder().prefix("llm").build().unwrap())
+        .into_iter()
+        .filter_map(|token| match token.kind {
+            ASLANTokenKind::Delimiter { prefix, .. } | ASLANTokenKind::ReservedDelimiter { prefix, .. } => Some(prefix),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(prefixes, vec!["llm", "llm"]);
+}
+
+#[test]
+fn routes_each_prefix_into_its_own_result() {
+    let mut router = ASLANPrefixRouter::new(&["aslan", "llm"]);
+    let results = router.parse(
+        "intro[asland_answer]See below[llmd_quote][llmo][llmd_name]Sub[llmo][asland_done]yes[llmd_extra]1",
+    );
+    assert_eq!(results, json!({
+        "aslan": { "_default": "intro", "answer": "See below", "done": "yes" },
+        "llm": { "_default": null, "quote": { "name": "Sub" }, "extra": "1" }
+    }));
+}
+
+#[test]
+fn routes_streamed_chunks_split_inside_delimiters() {
+    let input = "[asland_a]x[llmd_b]y[asl[asland_c]z [llm]";
+    let mut whole = ASLANPrefixRouter::new(&["aslan", "llm"]);
+    let expected = whole.parse(input);
+    assert_eq!(expected["aslan"], json!({ "_default": null, "a": "x", "c": "z [llm]" }));
+    assert_eq!(expected["llm"], json!({ "_default": null, "b": "y[asl" }));
+
+    for split in 0..input.len() {
+        let mut router = ASLANPrefixRouter::new(&["aslan", "llm"]);
+        router.parse_next(&input[..split]);
+        router.parse_next(&input[split..]);
+        router.close();
+        assert_eq!(router.get_results(), expected, "split at {}", split);
+    }
+}
+
+#[test]
+fn routes_delimiters_read_with_the_given_settings() {
+    let settings = ASLANParserSettings::builder()
+        .tolerance(ASLANTolerance { space_after_prefix: true, ..Default::default() })
+        .quoted_args(true)
+        .build()
+        .unwrap();
+    let mut router = ASLANPrefixRouter::with_settings(settings, &["aslan", "llm"]);
+    let results = router.parse("[aslan d_a]1[llm d_b]2[llmi_x:\"a]b\"]3");
+    assert_eq!(results, json!({
+        "aslan": { "_default": null, "a": "1" },
+        "llm": { "_default": null, "b": "23" }
+    }));
+}
+
+#[test]
+fn exposes_each_prefix_parser_for_listeners() {
+    let mut router = ASLANPrefixRouter::with_settings(
+        ASLANParserSettings { strict_end: true, ..Default::default() },
+        &["aslan", "llm"],
+    );
+    let fields = Rc::new(RefCell::new(Vec::new()));
+    let fields_clone = fields.clone();
+    router
+        .parser_mut("llm")
+        .unwrap()
+        .add_end_data_listener(move |event| fields_clone.borrow_mut().push(event.field_name.clone()));
+
+    router.parse_next("[asland_a]1[llmd_b]2");
+    assert_eq!(router.active_prefix(), "llm");
+    router.parse_next("[llms][asland_c]3");
+    router.close();
+    assert_eq!(router.parser("llm").unwrap().get_results()[0]["b"], json!("2"));
+    assert_eq!(router.get_result("aslan").unwrap()["c"], json!("3"));
+    assert!(fields.borrow().contains(&"b".to_string()));
+    assert!(router.get_result("xml").is_none());
+}
@@ -0,0 +1,89 @@
+use aslan::prompt::describe;
+use aslan::{ASLANParser, ASLANParserSettings, ASLANSchema};
+
+fn example_section(prompt: &str) -> &str {
+    prompt.split("\nExample:\n").nth(1).unwrap()
+}
+
+#[test]
+fn mentions_only_used_features() {
+    let schema = ASLANSchema::object().with_field("answer", ASLANSchema::string());
+    let prompt = describe(&schema, &ASLANParserSettings::default());
+    assert!(prompt.contains("`[asland_NAME]` starts the field NAME"));
+    assert!(prompt.contains("stored as `_default`"));
+    for delimiter in ["[aslano]", "[aslana]", "[aslanp]", "[aslani_", "[aslanv]", "[aslane_", "[aslang]", "[aslans]"] {
+        assert!(!prompt.contains(delimiter), "unexpected {} in prompt:\n{}", delimiter, prompt);
+    }
+    assert!(prompt.ends_with("Example:\n[asland_answer]example answer\n"));
+}
+
+#[test]
+fn explains_every_feature_the_schema_uses() {
+    let schema = ASLANSchema::object()
+        .with_field("steps", ASLANSchema::array(ASLANSchema::parts().with_instructions(&["bold", "italic"])))
+        .with_field("meta", ASLANSchema::object().with_optional_field("note", ASLANSchema::string().nullable()))
+        .with_field("code", ASLANSchema::string().escaped());
+    let settings = ASLANParserSettings {
+        prefix: "zz".to_string(),
+        default_field_name: "preamble".to_string(),
+        strict_start: true,
+        strict_end: true,
+        ..Default::default()
+    };
+    let prompt = describe(&schema, &settings);
+    for text in [
+        "`[zzg]`",
+        "`[zzd_NAME]`",
+        "`[zzo]`",
+        "`[zza]`",
+        "`[zzp]`",
+        "Available instructions: `bold`, `italic`.",
+        "`[zzv]`",
+        "`[zze_TAG]`",
+        "`[zzs]`",
+        "- `code`: text, escaped (required)",
+    ] {
+        assert!(prompt.contains(text), "missing {} in prompt:\n{}", text, prompt);
+    }
+    // With strict_start the default field is not reachable, so it is not mentioned
+    assert!(!prompt.contains("preamble"));
+    assert!(example_section(&prompt).starts_with("[zzg][zzd_steps][zza]"));
+}
+
+#[test]
+fn places_instructions_of_text_fields_inside_the_text() {
+    let schema = ASLANSchema::object().with_field("answer", ASLANSchema::string().with_instructions(&["cite"]));
+    let prompt = describe(&schema, &ASLANParserSettings::default());
+    assert!(prompt.contains("`[aslani_NAME]` inside the field's text applies the instruction NAME at that point."), "{}", prompt);
+    assert!(!prompt.contains("[aslanp]"), "unexpected parts delimiter in prompt:\n{}", prompt);
+
+    let schema = schema.with_field("body", ASLANSchema::parts().with_instructions(&["bold"]));
+    let prompt = describe(&schema, &ASLANParserSettings::default());
+    assert!(prompt.contains("right after `[aslanp]` applies the instruction NAME to that part. In fields without parts"), "{}", prompt);
+}
+
+#[test]
+fn example_parses_back_without_violations() {
+    let schema = ASLANSchema::object()
+        .with_field("title", ASLANSchema::string().with_allowed_values(&["draft", "final"]))
+        .with_field("items", ASLANSchema::array(ASLANSchema::object().with_field("name", ASLANSchema::string())).closed())
+        .with_field("body", ASLANSchema::parts().with_instructions(&["bold"]))
+        .with_field("code", ASLANSchema::string().escaped())
+        .closed();
+    let settings = ASLANParserSettings {
+        prefix: "out".to_string(),
+        strict_start: true,
+        strict_end: true,
+        ..Default::default()
+    };
+    let prompt = describe(&schema, &settings);
+
+    let mut parser = ASLANParser::with_settings(settings);
+    parser.parse(&format!("Sure, here it is:\n{}\nHope this helps!", example_section(&prompt)));
+    // The epilogue after the stop delimiter starts a second result
+    let result = parser.get_results()[0].clone();
+    assert_eq!(schema.validate(&result), vec![]);
+    assert_eq!(result["title"], "draft\n");
+    assert_eq!(result["code"], "example code\n");
+    assert_eq!(result["items"].as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,92 @@
+use aslan::{
+    ASLANDelimiterExtension, ASLANDiagnosticCode, ASLANExtensionContext, ASLANExtensionDelimiter, ASLANExtensionGrammar,
+    ASLANParser, ASLANParserSettings,
+};
+use serde_json::json;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn quoted_parser() -> ASLANParser {
+    ASLANParser::with_settings(ASLANParserSettings::builder().quoted_args(true).build().unwrap())
+}
+
+fn instruction_args(parser: &mut ASLANParser, input: &str) -> Vec<Vec<String>> {
+    let args: Rc<RefCell<Vec<Vec<String>>>> = Rc::new(RefCell::new(Vec::new()));
+    let args_clone = args.clone();
+    parser.add_end_data_listener(move |event| {
+        for part in &event.content {
+            for instruction in &part.instructions {
+                args_clone.borrow_mut().push(instruction.args.clone());
+            }
+        }
+    });
+    parser.parse(input);
+    let args = args.borrow().clone();
+    args
+}
+
+#[test]
+fn splits_args_on_every_colon_by_default() {
+    let mut parser = ASLANParser::new();
+    let args = instruction_args(&mut parser, "[asland_body]x[aslani_link:https://example.com/a]");
+    assert_eq!(args, vec![vec!["https".to_string(), "//example.com/a".to_string()]]);
+}
+
+#[test]
+fn keeps_colons_and_brackets_inside_quotes() {
+    let mut parser = quoted_parser();
+    let args = instruction_args(&mut parser, "[asland_body]x[aslani_link:\"https://example.com/[a]\":_blank]y");
+    assert_eq!(args, vec![vec!["https://example.com/[a]".to_string(), "_blank".to_string()]]);
+    assert_eq!(parser.get_result(), json!({ "_default": null, "body": "xy" }));
+}
+
+#[test]
+fn takes_the_char_after_a_backslash_literally() {
+    let mut parser = quoted_parser();
+    let args = instruction_args(&mut parser, "[asland_body]x[aslani_at:12\\:30:a\\]b:\"say \\\"hi\\\"\":c\\\\]");
+    assert_eq!(args, vec![vec!["12:30".to_string(), "a]b".to_string(), "say \"hi\"".to_string(), "c\\".to_string()]]);
+}
+
+#[test]
+fn rejects_text_after_the_closing_quote() {
+    let mut parser = quoted_parser();
+    let result = parser.parse("[asland_body]a[aslani_link:\"u\"x]b");
+    assert_eq!(result, json!({ "_default": null, "body": "a[aslani_link:\"u\"x]b" }));
+    assert!(parser.diagnostics().iter().any(|d| d.code == ASLANDiagnosticCode::MalformedDelimiter));
+}
+
+#[test]
+fn reads_quoted_data_args_while_streaming() {
+    let mut parser = quoted_parser();
+    for ch in "[asland_title:\"l\"]One[asland_title:\"l\"]Two".chars() {
+        parser.parse_next(&ch.to_string());
+    }
+    parser.close();
+    assert_eq!(parser.get_result()["title"], "Two");
+}
+
+struct Links(Rc<RefCell<Vec<String>>>);
+
+impl ASLANDelimiterExtension for Links {
+    fn suffix(&self) -> char {
+        'u'
+    }
+
+    fn grammar(&self) -> ASLANExtensionGrammar {
+        ASLANExtensionGrammar { args: true, ..Default::default() }
+    }
+
+    fn handle(&mut self, delimiter: &ASLANExtensionDelimiter, _: &mut ASLANExtensionContext) {
+        self.0.borrow_mut().extend(delimiter.args.iter().cloned());
+    }
+}
+
+#[test]
+fn reads_quoted_args_of_custom_delimiters() {
+    let urls = Rc::new(RefCell::new(Vec::new()));
+    let mut parser = quoted_parser();
+    parser.register_extension(Links(urls.clone())).unwrap();
+    let result = parser.parse("[asland_body]See[aslanu:\"https://example.com\"] docs");
+    assert_eq!(result["body"], "See docs");
+    assert_eq!(*urls.borrow(), vec!["https://example.com".to_string()]);
+}
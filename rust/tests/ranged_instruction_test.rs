@@ -0,0 +1,92 @@
+use aslan::{
+    ASLANDiagnosticCode, ASLANEndDataInstruction, ASLANIndexUnit, ASLANInstruction, ASLANInstructionInfo, ASLANParser,
+    ASLANParserSettings,
+};
+use serde_json::json;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn ranged_parser() -> ASLANParser {
+    ASLANParser::with_settings(ASLANParserSettings::builder().ranged_instructions(true).build().unwrap())
+}
+
+/// Instructions of each part of `field` in its end_data event
+fn parts(parser: &mut ASLANParser, input: &str, field: &str) -> Vec<Vec<ASLANInstructionInfo>> {
+    let events: Rc<RefCell<Vec<ASLANEndDataInstruction>>> = Rc::new(RefCell::new(Vec::new()));
+    let events_clone = events.clone();
+    parser.add_end_data_listener(move |event| events_clone.borrow_mut().push(event.clone()));
+    parser.parse(input);
+    let events = events.borrow();
+    let event = events.iter().find(|event| event.field_name == field).unwrap();
+    event.content.iter().map(|part| part.instructions.clone()).collect()
+}
+
+#[test]
+fn matches_start_and_end_markers() {
+    let mut parser = ranged_parser();
+    let parts = parts(&mut parser, "[asland_body]Make [aslani_bold:start]this[aslani_bold:end] bold", "body");
+    assert_eq!(parser.get_result(), json!({ "_default": null, "body": "Make this bold" }));
+    assert_eq!(parts[0].len(), 1);
+    assert_eq!(parts[0][0].name, "bold");
+    assert!(parts[0][0].args.is_empty());
+    assert_eq!(parts[0][0].index, 5);
+    assert_eq!(parts[0][0].range, Some((5, 9)));
+}
+
+#[test]
+fn closes_open_ranges_at_the_end_of_their_part() {
+    let mut parser = ranged_parser();
+    let parts = parts(&mut parser, "[asland_body][aslanp]One [aslani_em:start]two[aslanp]three[aslani_em:end]", "body");
+    assert_eq!(parts[0][0].range, Some((4, 7)));
+    assert!(parts[1].is_empty());
+    let diagnostics = parser.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, ASLANDiagnosticCode::UnmatchedRangeEnd);
+}
+
+#[test]
+fn pairs_nested_ranges_innermost_first() {
+    let mut parser = ranged_parser();
+    let parts = parts(&mut parser, "[asland_body][aslani_b:start]a[aslani_b:start]b[aslani_b:end]c[aslani_b:end]", "body");
+    let ranges: Vec<_> = parts[0].iter().map(|instruction| instruction.range).collect();
+    assert_eq!(ranges, vec![Some((0, 3)), Some((1, 2))]);
+}
+
+#[test]
+fn keeps_args_after_the_start_marker_and_point_instructions() {
+    let mut parser = ranged_parser();
+    let parts = parts(&mut parser, "[asland_body]See [aslani_link:start:docs]here[aslani_link:end][aslani_br]", "body");
+    assert_eq!(parts[0][0].args, vec!["docs"]);
+    assert_eq!(parts[0][0].range, Some((4, 8)));
+    assert_eq!(parts[0][1].name, "br");
+    assert_eq!(parts[0][1].range, None);
+}
+
+#[test]
+fn reports_growing_ranges_in_content_events() {
+    let settings = ASLANParserSettings::builder()
+        .ranged_instructions(true)
+        .instruction_index_unit(ASLANIndexUnit::Utf16)
+        .build()
+        .unwrap();
+    let mut parser = ASLANParser::with_settings(settings);
+    let events: Rc<RefCell<Vec<ASLANInstruction>>> = Rc::new(RefCell::new(Vec::new()));
+    let events_clone = events.clone();
+    parser.add_content_listener(move |event| events_clone.borrow_mut().push(event.clone()));
+
+    parser.parse_next("[asland_body]\u{1F600} [aslani_bold:start]");
+    assert_eq!(events.borrow().last().unwrap().range, Some((3, 3)));
+    parser.parse_next("\u{1F600}\u{1F600}[aslani_bold:end]!");
+    assert_eq!(events.borrow().last().unwrap().range, Some((3, 7)));
+    parser.close();
+}
+
+#[test]
+fn leaves_markers_as_args_by_default() {
+    let mut parser = ASLANParser::new();
+    let parts = parts(&mut parser, "[asland_body]Make [aslani_bold:start]this[aslani_bold:end] bold", "body");
+    assert_eq!(parts[0].len(), 2);
+    assert_eq!(parts[0][0].args, vec!["start"]);
+    assert_eq!(parts[0][1].args, vec!["end"]);
+    assert!(parts[0].iter().all(|instruction| instruction.range.is_none()));
+}
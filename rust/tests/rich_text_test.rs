@@ -0,0 +1,137 @@
+use aslan::{
+    ASLANContentPart, ASLANEndDataInstruction, ASLANIndexUnit, ASLANInstructionInfo, ASLANParser, ASLANParserSettings,
+    ASLANRichText, ASLANRichTextNode,
+};
+use serde_json::json;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn rich_text(settings: ASLANParserSettings, input: &str, field: &str) -> ASLANRichText {
+    let mut parser = ASLANParser::with_settings(settings);
+    let events: Rc<RefCell<Vec<ASLANEndDataInstruction>>> = Rc::new(RefCell::new(Vec::new()));
+    let events_clone = events.clone();
+    parser.add_end_data_listener(move |event| events_clone.borrow_mut().push(event.clone()));
+    parser.parse(input);
+    let events = events.borrow();
+    let event = events.iter().find(|event| event.field_name == field).unwrap();
+    ASLANRichText::from_end_data(event)
+}
+
+fn ranged() -> ASLANParserSettings {
+    ASLANParserSettings::builder().ranged_instructions(true).build().unwrap()
+}
+
+fn instruction(name: &str, index: usize, range: Option<(usize, usize)>) -> ASLANInstructionInfo {
+    ASLANInstructionInfo {
+        name: name.to_string(),
+        args: Vec::new(),
+        named_args: Default::default(),
+        index,
+        range,
+    }
+}
+
+#[test]
+fn places_points_and_ranges_in_the_text() {
+    let text = rich_text(ranged(), "[asland_body]Make [aslani_bold:start]this[aslani_bold:end] bold[aslani_br]", "body");
+    let part = serde_json::to_value(&text.parts[0]).unwrap();
+    assert_eq!(part["text"], "Make this bold");
+    assert_eq!(part["instructions"][0]["range"], json!([5, 9]));
+    assert_eq!(
+        part["children"],
+        json!([
+            { "Text": "Make " },
+            { "Range": { "name": "bold", "args": [], "named_args": {}, "children": [{ "Text": "this" }] } },
+            { "Text": " bold" },
+            { "Instruction": { "name": "br", "args": [], "named_args": {} } }
+        ])
+    );
+}
+
+#[test]
+fn nests_ranges_and_closes_them_at_the_end_of_their_part() {
+    let input = "[asland_body][aslanp][aslani_quote:start]Say [aslani_em:start]hi[aslani_em:end][aslanp]Bye";
+    let text = rich_text(ranged(), input, "body");
+    assert_eq!(text.parts.len(), 2);
+    assert_eq!(
+        text.parts[0].children,
+        vec![ASLANRichTextNode::Range {
+            name: "quote".to_string(),
+            args: Vec::new(),
+            named_args: Default::default(),
+            children: vec![
+                ASLANRichTextNode::Text("Say ".to_string()),
+                ASLANRichTextNode::Range {
+                    name: "em".to_string(),
+                    args: Vec::new(),
+                    named_args: Default::default(),
+                    children: vec![ASLANRichTextNode::Text("hi".to_string())],
+                },
+            ],
+        }]
+    );
+    assert_eq!(text.parts[1].children, vec![ASLANRichTextNode::Text("Bye".to_string())]);
+}
+
+#[test]
+fn splits_overlapping_ranges() {
+    let part = ASLANContentPart {
+        value: "abcdefgh".to_string(),
+        part_index: 0,
+        instructions: vec![instruction("a", 0, Some((0, 5))), instruction("b", 3, Some((3, 8)))],
+    };
+    let text = ASLANRichText::from_parts(&[part], ASLANIndexUnit::Bytes);
+    assert_eq!(
+        serde_json::to_value(&text.parts[0].children).unwrap(),
+        json!([
+            { "Range": { "name": "a", "args": [], "named_args": {}, "children": [
+                { "Text": "abc" },
+                { "Range": { "name": "b", "args": [], "named_args": {}, "children": [{ "Text": "de" }] } }
+            ] } },
+            { "Range": { "name": "b", "args": [], "named_args": {}, "children": [{ "Text": "fgh" }] } }
+        ])
+    );
+}
+
+#[test]
+fn reads_offsets_in_the_index_unit() {
+    let settings = ASLANParserSettings::builder()
+        .ranged_instructions(true)
+        .instruction_index_unit(ASLANIndexUnit::Utf16)
+        .build()
+        .unwrap();
+    let input = "[asland_body]\u{1F600} [aslani_b:start]\u{e9}t\u{e9}[aslani_b:end]\u{1F1EB}\u{1F1F7}";
+    let text = rich_text(settings, input, "body");
+    assert_eq!(text.parts[0].instructions[0].range, Some((3, 6)));
+    assert_eq!(
+        text.parts[0].children,
+        vec![
+            ASLANRichTextNode::Text("\u{1F600} ".to_string()),
+            ASLANRichTextNode::Range {
+                name: "b".to_string(),
+                args: Vec::new(),
+                named_args: Default::default(),
+                children: vec![ASLANRichTextNode::Text("\u{e9}t\u{e9}".to_string())],
+            },
+            ASLANRichTextNode::Text("\u{1F1EB}\u{1F1F7}".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn builds_plain_rich_text_from_result_values() {
+    let text = ASLANRichText::from_value(&json!(["One", "Two"])).unwrap();
+    assert_eq!(text.parts.len(), 2);
+    assert_eq!(text.parts[1].part_index, 1);
+    assert_eq!(text.parts[1].children, vec![ASLANRichTextNode::Text("Two".to_string())]);
+    assert_eq!(ASLANRichText::from_value(&json!("")).unwrap().parts[0].children, Vec::new());
+    assert!(ASLANRichText::from_value(&json!({ "a": "b" })).is_none());
+    assert!(ASLANRichText::from_value(&json!(["a", null])).is_none());
+}
+
+#[test]
+fn round_trips_through_serde() {
+    let text = rich_text(ranged(), "[asland_body]x[aslani_link:start:docs]y[aslani_link:end]", "body");
+    let json = serde_json::to_string(&text).unwrap();
+    assert_eq!(serde_json::from_str::<ASLANRichText>(&json).unwrap(), text);
+}
@@ -0,0 +1,115 @@
+use aslan::{ASLANParser, ASLANParserSettings, ASLANSchema};
+use serde_json::json;
+
+fn edits_schema() -> ASLANSchema {
+    ASLANSchema::object()
+        .with_field("edit1", ASLANSchema::object().with_field("text", ASLANSchema::string()))
+        .with_field("edit2", ASLANSchema::object().with_field("text", ASLANSchema::string()))
+}
+
+#[test]
+fn schema_resolves_empty_field_object_close() {
+    // Same input as the max_object_depth bug case, resolved without a depth cap
+    let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+        schema: Some(edits_schema()),
+        ..Default::default()
+    });
+    let result = parser.parse(
+        "[asland_edit1][aslano]\n\
+         [asland_text]\n\
+         [aslano]\n\
+         \n\
+         [asland_edit2][aslano]\n\
+         [asland_text]This content is correctly placed\n\
+         [aslano]",
+    );
+    assert_eq!(result, json!({
+        "_default": null,
+        "edit1": {
+            "text": "\n"
+        },
+        "edit2": {
+            "text": "This content is correctly placed\n"
+        }
+    }));
+}
+
+#[test]
+fn schema_opens_object_after_whitespace_with_collapse_disabled() {
+    let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+        collapse_object_start_whitespace: false,
+        schema: Some(ASLANSchema::object().with_field("edit1", ASLANSchema::object().with_field("text", ASLANSchema::string()))),
+        ..Default::default()
+    });
+    let result = parser.parse(
+        "[asland_edit1]\n\
+         [aslano]\n\
+         [asland_text]Some text\n\
+         [aslano]",
+    );
+    assert_eq!(result, json!({
+        "_default": null,
+        "edit1": {
+            "text": "Some text\n"
+        }
+    }));
+}
+
+#[test]
+fn schema_allows_deeper_nesting_than_max_object_depth_workaround() {
+    let schema = ASLANSchema::object().with_field(
+        "a",
+        ASLANSchema::object()
+            .with_field("b", ASLANSchema::object().with_field("c", ASLANSchema::string()))
+            .with_field("d", ASLANSchema::string()),
+    );
+    let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+        schema: Some(schema),
+        ..Default::default()
+    });
+    let result = parser.parse("[asland_a][aslano][asland_b][aslano][asland_c] [aslano][asland_d]x[aslano][aslano][aslano]");
+    assert_eq!(result, json!({
+        "_default": null,
+        "a": {
+            "b": {
+                "c": " "
+            },
+            "d": "x"
+        }
+    }));
+}
+
+#[test]
+fn schema_resolves_nested_arrays() {
+    let schema = ASLANSchema::object()
+        .with_field("rows", ASLANSchema::array(ASLANSchema::array(ASLANSchema::string())))
+        .with_field("after", ASLANSchema::string());
+    let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+        schema: Some(schema),
+        ..Default::default()
+    });
+    let result = parser.parse(
+        "[asland_rows][aslana][asland][aslana][asland] [aslana][asland][aslana][asland]b[aslana][aslana][asland_after]done",
+    );
+    assert_eq!(result, json!({
+        "_default": null,
+        "rows": [[" "], ["b"]],
+        "after": "done"
+    }));
+}
+
+#[test]
+fn falls_back_to_heuristics_for_keys_outside_schema() {
+    let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+        schema: Some(ASLANSchema::object()),
+        ..Default::default()
+    });
+    let result = parser.parse("[asland_foo][aslano][asland_bar]Baz![aslano][asland_x]y");
+    assert_eq!(result, json!({
+        "_default": null,
+        "foo": {
+            "bar": "Baz!"
+        },
+        "x": "y"
+    }));
+}
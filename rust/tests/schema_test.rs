@@ -0,0 +1,144 @@
+use aslan::{ASLANEndDataInstruction, ASLANParser, ASLANSchema, ASLANSchemaType, ASLANViolationKind};
+use serde_json::json;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn article_schema() -> ASLANSchema {
+    ASLANSchema::object()
+        .with_field("title", ASLANSchema::string())
+        .with_field("content", ASLANSchema::parts().with_instructions(&["bold"]))
+        .with_optional_field("tags", ASLANSchema::array(ASLANSchema::string()).with_item_bounds(None, Some(2)))
+        .with_optional_field("author", ASLANSchema::object().with_field("name", ASLANSchema::string()).closed().nullable())
+        .closed()
+}
+
+#[test]
+fn validates_matching_parse_result() {
+    let mut parser = ASLANParser::new();
+    let result = parser.parse(
+        "[asland_title]Hi[asland_content][aslanp]One[aslanp]Two[asland_tags][aslana][asland]a[asland]b[aslana][asland_author][aslanv]",
+    );
+    assert_eq!(article_schema().validate(&result), vec![]);
+}
+
+#[test]
+fn reports_path_precise_violations() {
+    let mut parser = ASLANParser::new();
+    let result = parser.parse(
+        "[asland_content][aslano][asland_x]y[aslano][asland_tags][aslana][asland]a[asland]b[asland]c[aslana][asland_author][aslano][asland_nom]Bob[aslano][asland_extra]!",
+    );
+    let violations = article_schema().validate(&result);
+    let summary: Vec<(ASLANViolationKind, Vec<String>)> =
+        violations.iter().map(|v| (v.kind, v.path.clone())).collect();
+    assert_eq!(
+        summary,
+        vec![
+            (ASLANViolationKind::MissingField, vec!["title".to_string()]),
+            (ASLANViolationKind::TypeMismatch, vec!["content".to_string()]),
+            (ASLANViolationKind::TooManyItems, vec!["tags".to_string()]),
+            (ASLANViolationKind::MissingField, vec!["author".to_string(), "name".to_string()]),
+            (ASLANViolationKind::UnknownField, vec!["author".to_string(), "nom".to_string()]),
+            (ASLANViolationKind::UnknownField, vec!["extra".to_string()]),
+        ]
+    );
+    assert_eq!(violations[1].to_string(), "content: expected string or parts but found object");
+}
+
+#[test]
+fn reports_unexpected_null_and_disallowed_values() {
+    let schema = ASLANSchema::object()
+        .with_field("mood", ASLANSchema::string().with_allowed_values(&["happy", "sad"]))
+        .with_field("note", ASLANSchema::string());
+    let mut parser = ASLANParser::new();
+    let result = parser.parse("[asland_mood]angry[asland_note][aslanv]");
+    let kinds: Vec<ASLANViolationKind> = schema.validate(&result).iter().map(|v| v.kind).collect();
+    assert_eq!(kinds, vec![ASLANViolationKind::DisallowedValue, ASLANViolationKind::UnexpectedNull]);
+}
+
+#[test]
+fn reports_disallowed_instructions_of_completed_fields() {
+    let events: Rc<RefCell<Vec<ASLANEndDataInstruction>>> = Rc::new(RefCell::new(Vec::new()));
+    let events_clone = events.clone();
+    let mut parser = ASLANParser::new();
+    parser.add_end_data_listener(move |event| events_clone.borrow_mut().push(event.clone()));
+    parser.parse("[asland_title]Hi[aslani_bold][asland_content][aslanp]One[aslani_bold][aslanp]Two[aslani_italic]");
+
+    let schema = article_schema();
+    let violations: Vec<_> = events.borrow().iter().flat_map(|event| schema.validate_end_data(event)).collect();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].kind, ASLANViolationKind::DisallowedInstruction);
+    assert_eq!(violations[0].to_string(), "content: instruction `italic` is not one of [\"bold\"]");
+}
+
+#[test]
+fn ignores_renamed_default_field() {
+    let schema = ASLANSchema::object().with_field("a", ASLANSchema::string()).closed();
+    let value = json!({ "preamble": "Sure!", "a": "x" });
+    assert_eq!(schema.validate_with_default_field(&value, "preamble"), vec![]);
+    assert_eq!(schema.validate(&value).len(), 1);
+}
+
+#[test]
+fn deserializes_schemas_with_omitted_keys() {
+    let schema: ASLANSchema = serde_json::from_value(json!({
+        "schema_type": "Object",
+        "fields": [{ "name": "title" }, { "name": "tags", "schema": { "schema_type": "Array" }, "required": false }]
+    }))
+    .unwrap();
+    assert_eq!(
+        schema,
+        ASLANSchema::object().with_field("title", ASLANSchema::string()).with_optional_field("tags", ASLANSchema { schema_type: ASLANSchemaType::Array, ..Default::default() })
+    );
+    assert_eq!(serde_json::from_value::<ASLANSchema>(json!({})).unwrap(), ASLANSchema::string());
+}
+
+#[test]
+fn loads_json_schema_subset() {
+    let schema = ASLANSchema::from_json_schema(&json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["title", "items"],
+        "properties": {
+            "title": { "type": "string", "description": "The title" },
+            "body": { "type": "array", "items": { "type": "string" }, "x-aslan-parts": true, "x-aslan-instructions": ["bold"] },
+            "items": { "type": "array", "items": { "type": ["string", "null"] }, "maxItems": 3 },
+            "meta": { "anyOf": [{ "type": "object", "properties": {} }, { "type": "null" }] },
+            "kind": { "enum": ["a", "b"] },
+            "code": { "type": "string", "x-aslan-escaped": true }
+        }
+    }))
+    .unwrap();
+
+    assert_eq!(schema.schema_type, ASLANSchemaType::Object);
+    assert!(!schema.additional_fields);
+    let title = schema.get_field("title").unwrap();
+    assert!(title.required);
+    assert_eq!(title.schema.description.as_deref(), Some("The title"));
+    assert!(!title.schema.escaped);
+    assert!(schema.get_field("code").unwrap().schema.escaped);
+    let body = schema.get_field("body").unwrap();
+    assert!(!body.required);
+    assert_eq!(body.schema.schema_type, ASLANSchemaType::Parts);
+    assert_eq!(body.schema.allowed_instructions, Some(vec!["bold".to_string()]));
+    let items = &schema.get_field("items").unwrap().schema;
+    assert_eq!(items.max_items, Some(3));
+    assert!(items.items.as_ref().unwrap().nullable);
+    let meta = &schema.get_field("meta").unwrap().schema;
+    assert_eq!(meta.schema_type, ASLANSchemaType::Object);
+    assert!(meta.nullable);
+    assert_eq!(
+        schema.get_field("kind").unwrap().schema.allowed_values,
+        Some(vec!["a".to_string(), "b".to_string()])
+    );
+}
+
+#[test]
+fn rejects_unsupported_json_schema() {
+    let err = ASLANSchema::from_json_schema(&json!({
+        "type": "object",
+        "properties": { "x": { "type": "tuple" } }
+    }))
+    .unwrap_err();
+    assert_eq!(err.path, vec!["x".to_string()]);
+    assert_eq!(err.to_string(), "invalid schema at x: unsupported type `tuple`");
+}
@@ -0,0 +1,99 @@
+use aslan::{ASLANParser, ASLANParserSettings, ASLANSchema, ASLANSchemaType};
+use serde_json::json;
+
+#[test]
+fn builds_valid_settings() {
+    let settings = ASLANParserSettings::builder()
+        .prefix("llm")
+        .strict_end(true)
+        .max_object_depth(Some(1))
+        .build()
+        .unwrap();
+    assert_eq!(settings.prefix, "llm");
+    assert!(settings.strict_end);
+    assert_eq!(settings.max_object_depth, Some(1));
+    assert_eq!(settings.default_field_name, "_default");
+
+    let mut parser = ASLANParser::with_settings(settings);
+    parser.parse("[llmd_a]1[llms]");
+    assert_eq!(parser.get_results()[0]["a"], json!("1"));
+}
+
+#[test]
+fn rejects_invalid_options() {
+    let cases = [
+        (ASLANParserSettings::builder().prefix(""), "prefix"),
+        (ASLANParserSettings::builder().prefix("as-lan"), "prefix"),
+        (ASLANParserSettings::builder().prefix("äslan"), "prefix"),
+        (ASLANParserSettings::builder().default_field_name(""), "default_field_name"),
+        (ASLANParserSettings::builder().default_field_name("my field"), "default_field_name"),
+        (ASLANParserSettings::builder().max_object_depth(Some(0)), "max_object_depth"),
+        (
+            ASLANParserSettings::builder().max_object_depth(Some(2)).collapse_object_start_whitespace(false),
+            "max_object_depth",
+        ),
+        (ASLANParserSettings::builder().coerce_leaves(true), "coerce_leaves"),
+    ];
+    for (builder, field) in cases {
+        let error = builder.build().unwrap_err();
+        assert_eq!(error.field, field, "{}", error);
+    }
+    assert_eq!(
+        ASLANParserSettings::builder().prefix("as-lan").build().unwrap_err().to_string(),
+        "invalid setting `prefix`: `as-lan` must be a non-empty string of ASCII letters and digits"
+    );
+}
+
+#[test]
+fn unsets_options_in_the_builder() {
+    let settings = ASLANParserSettings::builder()
+        .max_object_depth(Some(1))
+        .max_object_depth(None)
+        .collapse_object_start_whitespace(false)
+        .build()
+        .unwrap();
+    assert_eq!(settings.max_object_depth, None);
+}
+
+#[test]
+fn checks_settings_passed_to_the_parser() {
+    let settings = ASLANParserSettings {
+        coerce_leaves: true,
+        ..Default::default()
+    };
+    let error = ASLANParser::try_with_settings(settings).err().unwrap();
+    assert_eq!(error.field, "coerce_leaves");
+    assert!(ASLANParser::try_with_settings(ASLANParserSettings::default()).is_ok());
+}
+
+#[test]
+fn round_trips_through_json() {
+    let settings = ASLANParserSettings::builder()
+        .prefix("llm")
+        .schema(ASLANSchema::from_type_map([("count", ASLANSchemaType::Integer)]))
+        .coerce_leaves(true)
+        .append_separator("\n")
+        .build()
+        .unwrap();
+    let json = serde_json::to_value(&settings).unwrap();
+    assert_eq!(json["prefix"], json!("llm"));
+    assert_eq!(json["emittable_events"], json!({ "content": true, "end": true, "end_data": true }));
+
+    let settings: ASLANParserSettings = serde_json::from_value(json).unwrap();
+    let mut parser = ASLANParser::with_settings(settings);
+    assert_eq!(parser.parse("[llmd_count] 3 ")["count"], json!(3));
+}
+
+#[test]
+fn deserializes_partial_configs_with_defaults_and_validates_them() {
+    let settings: ASLANParserSettings =
+        serde_json::from_str(r#"{ "prefix": "llm", "emittable_events": { "content": false } }"#).unwrap();
+    assert_eq!(settings.prefix, "llm");
+    assert_eq!(settings.default_field_name, "_default");
+    assert!(settings.collapse_object_start_whitespace);
+    assert!(!settings.emittable_events.content);
+    assert!(settings.emittable_events.end);
+
+    let error = serde_json::from_str::<ASLANParserSettings>(r#"{ "prefix": "[x]" }"#).unwrap_err();
+    assert!(error.to_string().contains("invalid setting `prefix`"), "{}", error);
+}
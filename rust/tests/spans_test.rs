@@ -0,0 +1,200 @@
+use aslan::generator::ASLANGenerator;
+use aslan::{ASLANEndDataInstruction, ASLANParser, ASLANParserSettings, ASLANSchema, ASLANSchemaType, ASLANValueSpans};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn span_parser() -> ASLANParser {
+    ASLANParser::with_settings(ASLANParserSettings {
+        track_spans: true,
+        ..Default::default()
+    })
+}
+
+fn spans_at<'a>(spans: &'a [ASLANValueSpans], path: &[&str]) -> &'a ASLANValueSpans {
+    spans
+        .iter()
+        .find(|spans| spans.path == path)
+        .unwrap_or_else(|| panic!("no spans for {:?}", path))
+}
+
+fn delimiter_texts<'a>(input: &'a str, spans: &ASLANValueSpans) -> Vec<&'a str> {
+    spans.delimiters.iter().map(|span| &input[span.start..span.end]).collect()
+}
+
+fn content_texts<'a>(input: &'a str, spans: &ASLANValueSpans) -> Vec<&'a str> {
+    spans.content.iter().map(|span| &input[span.start..span.end]).collect()
+}
+
+#[test]
+fn records_delimiter_and_content_ranges_of_fields() {
+    let input = "Sure![asland_title]Pancakes[asland_serves:f]4[aslanc]two adults[asland_note][aslanv]";
+    let mut parser = span_parser();
+    parser.parse(input);
+    let spans = parser.spans();
+
+    assert_eq!(content_texts(input, spans_at(&spans, &["_default"])), vec!["Sure!"]);
+    let title = spans_at(&spans, &["title"]);
+    assert_eq!(delimiter_texts(input, title), vec!["[asland_title]"]);
+    assert_eq!(content_texts(input, title), vec!["Pancakes"]);
+    let serves = spans_at(&spans, &["serves"]);
+    assert_eq!(delimiter_texts(input, serves), vec!["[asland_serves:f]"]);
+    assert_eq!(content_texts(input, serves), vec!["4"]);
+    let note = spans_at(&spans, &["note"]);
+    assert_eq!(delimiter_texts(input, note), vec!["[asland_note]", "[aslanv]"]);
+    assert!(note.content.is_empty());
+}
+
+#[test]
+fn records_a_range_per_appended_duplicate_and_part() {
+    let input = "[asland_tag:a]red[asland_size]L[asland_tag:a]blue[asland_body]One[aslanp][aslani_bold]Two";
+    let mut parser = span_parser();
+    let result = parser.parse(input);
+    assert_eq!(result["tag"], json!("redblue"));
+    let spans = parser.spans();
+
+    let tag = spans_at(&spans, &["tag"]);
+    assert_eq!(delimiter_texts(input, tag), vec!["[asland_tag:a]", "[asland_tag:a]"]);
+    assert_eq!(content_texts(input, tag), vec!["red", "blue"]);
+    let body = spans_at(&spans, &["body"]);
+    assert_eq!(delimiter_texts(input, body), vec!["[asland_body]", "[aslanp]", "[aslani_bold]"]);
+    assert_eq!(content_texts(input, body), vec!["One", "Two"]);
+}
+
+#[test]
+fn only_keeps_the_last_range_of_keep_last_duplicates() {
+    let input = "[asland_a:l]old[asland_a]new";
+    let mut parser = span_parser();
+    parser.parse(input);
+    let spans = parser.spans();
+
+    let a = spans_at(&spans, &["a"]);
+    assert_eq!(delimiter_texts(input, a), vec!["[asland_a:l]", "[asland_a]"]);
+    assert_eq!(content_texts(input, a), vec!["new"]);
+}
+
+#[test]
+fn records_nested_objects_arrays_and_escapes() {
+    let input = "[asland_user][aslano][asland_name]Ada[aslano][asland_tags][aslana][asland]x[asland][aslane_T]y[z][aslane_T][aslana]";
+    let mut parser = span_parser();
+    let result = parser.parse(input);
+    assert_eq!(result["user"], json!({ "name": "Ada" }));
+    assert_eq!(result["tags"], json!(["x", "y[z]"]));
+    let spans = parser.spans();
+
+    assert_eq!(delimiter_texts(input, spans_at(&spans, &["user"])), vec!["[asland_user]", "[aslano]", "[aslano]"]);
+    assert_eq!(content_texts(input, spans_at(&spans, &["user", "name"])), vec!["Ada"]);
+    assert_eq!(delimiter_texts(input, spans_at(&spans, &["tags"])), vec!["[asland_tags]", "[aslana]", "[aslana]"]);
+    assert_eq!(content_texts(input, spans_at(&spans, &["tags", "0"])), vec!["x"]);
+    let second = spans_at(&spans, &["tags", "1"]);
+    assert_eq!(delimiter_texts(input, second), vec!["[asland]", "[aslane_T]", "[aslane_T]"]);
+    assert_eq!(content_texts(input, second), vec!["y[z]"]);
+}
+
+#[test]
+fn uses_byte_offsets_across_streamed_chunks() {
+    let input = "[asland_city]Zürich[asland_next]Genève";
+    let mut parser = span_parser();
+    for chunk in ["[asland_city]Zü", "rich[asla", "nd_next]Gen", "ève"] {
+        parser.parse_next(chunk);
+    }
+    parser.close();
+    let spans = parser.spans();
+
+    assert_eq!(content_texts(input, spans_at(&spans, &["city"])), vec!["Zürich"]);
+    let next = spans_at(&spans, &["next"]);
+    assert_eq!(delimiter_texts(input, next), vec!["[asland_next]"]);
+    assert_eq!(content_texts(input, next), vec!["Genève"]);
+}
+
+#[test]
+fn counts_offsets_from_the_start_of_the_input_after_reset() {
+    let input = "[asland_b]2";
+    let mut parser = span_parser();
+    parser.parse("[asland_a]1");
+    parser.reset();
+    parser.parse(input);
+    let spans = parser.spans();
+    assert_eq!(spans.len(), 1);
+    assert_eq!(delimiter_texts(input, spans_at(&spans, &["b"])), vec!["[asland_b]"]);
+}
+
+#[test]
+fn includes_spans_in_events_when_enabled() {
+    let input = "[asland_greeting]Hello [aslani_wave]world[asland_done]yes";
+    let events: Rc<RefCell<Vec<ASLANEndDataInstruction>>> = Rc::new(RefCell::new(Vec::new()));
+
+    let mut parser = span_parser();
+    let events_clone = events.clone();
+    parser.add_end_data_listener(move |event| events_clone.borrow_mut().push(event.clone()));
+    let content_spans = Rc::new(RefCell::new(Vec::new()));
+    let content_spans_clone = content_spans.clone();
+    parser.add_content_listener(move |event| content_spans_clone.borrow_mut().push(event.spans.clone()));
+    parser.parse(input);
+
+    let greeting = events.borrow().iter().find(|event| event.field_name == "greeting").unwrap().spans.clone().unwrap();
+    assert_eq!(greeting.path, vec!["greeting"]);
+    assert_eq!(delimiter_texts(input, &greeting), vec!["[asland_greeting]", "[aslani_wave]"]);
+    assert_eq!(content_texts(input, &greeting), vec!["Hello ", "world"]);
+    assert!(content_spans.borrow().iter().all(|spans| spans.is_some()));
+
+    let mut parser = ASLANParser::new();
+    let events_clone = events.clone();
+    events.borrow_mut().clear();
+    parser.add_end_data_listener(move |event| events_clone.borrow_mut().push(event.clone()));
+    parser.parse(input);
+    assert!(events.borrow().iter().all(|event| event.spans.is_none()));
+    assert!(parser.spans().is_empty());
+}
+
+fn assert_content_matches(input: &str, spans: &[ASLANValueSpans], path: &mut Vec<String>, value: &Value) {
+    let content = spans
+        .iter()
+        .find(|spans| spans.path == *path)
+        .map(|spans| content_texts(input, spans).concat())
+        .unwrap_or_default();
+    match value {
+        Value::String(s) => assert_eq!(&content, s, "at {:?}", path),
+        // Parts store their text directly on the field
+        Value::Array(items) if !content.is_empty() => {
+            let parts: Vec<&str> = items.iter().filter_map(|item| item.as_str()).collect();
+            assert_eq!(content, parts.concat(), "at {:?}", path);
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                path.push(i.to_string());
+                assert_content_matches(input, spans, path, item);
+                path.pop();
+            }
+        }
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                path.push(key.clone());
+                assert_content_matches(input, spans, path, field);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+#[test]
+fn content_ranges_of_generated_documents_match_parsed_values() {
+    let schema = ASLANSchema::object()
+        .with_field("title", ASLANSchema::string())
+        .with_field("body", ASLANSchema::parts().with_instructions(&["bold"]))
+        .with_field("author", ASLANSchema::object().with_field("name", ASLANSchema::string()).with_optional_field("email", ASLANSchema::string().nullable()))
+        .with_field("tags", ASLANSchema::array(ASLANSchema::string()))
+        .with_field("rows", ASLANSchema::array(ASLANSchema::array(ASLANSchema::scalar(ASLANSchemaType::Integer))))
+        .with_field("snippet", ASLANSchema::string().escaped());
+    for seed in 0..200 {
+        let document = ASLANGenerator::new(seed).generate(&schema);
+        let mut parser = span_parser();
+        for chunk in &document.chunks {
+            parser.parse_next(chunk);
+        }
+        parser.close();
+        let result = parser.get_result();
+        assert_content_matches(&document.text, &parser.spans(), &mut Vec::new(), &result);
+    }
+}
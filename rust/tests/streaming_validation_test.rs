@@ -0,0 +1,115 @@
+use aslan::{ASLANParser, ASLANParserSettings, ASLANSchema, ASLANViolation, ASLANViolationKind};
+use std::cell::RefCell;
+use std::ops::ControlFlow;
+use std::rc::Rc;
+
+fn recipe_schema() -> ASLANSchema {
+    ASLANSchema::object()
+        .with_field("title", ASLANSchema::string())
+        .with_field("steps", ASLANSchema::array(ASLANSchema::parts().with_instructions(&["bold"])).with_item_bounds(None, Some(2)))
+        .with_optional_field("note", ASLANSchema::string())
+        .closed()
+}
+
+fn recipe_parser() -> ASLANParser {
+    ASLANParser::with_settings(ASLANParserSettings {
+        schema: Some(recipe_schema()),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn emits_violation_event_when_unknown_field_delimiter_is_parsed() {
+    let mut parser = recipe_parser();
+    let events: Rc<RefCell<Vec<ASLANViolation>>> = Rc::new(RefCell::new(Vec::new()));
+    let events_clone = events.clone();
+    parser.add_violation_listener(move |violation| {
+        events_clone.borrow_mut().push(violation.clone());
+    });
+
+    parser.parse_next("[asland_title]Pancakes[asland_calories");
+    assert!(events.borrow().is_empty());
+    parser.parse_next("]");
+    assert_eq!(events.borrow().len(), 1);
+    assert_eq!(events.borrow()[0].kind, ASLANViolationKind::UnknownField);
+    assert_eq!(events.borrow()[0].path, vec!["calories".to_string()]);
+}
+
+#[test]
+fn reports_too_many_items_as_soon_as_extra_item_starts() {
+    let mut parser = recipe_parser();
+    parser.parse_next("[asland_title]x[asland_steps][aslana][asland]Mix[asland]Bake");
+    assert!(parser.get_violations().is_empty());
+    parser.parse_next("[asland]");
+    let violations = parser.get_violations();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].kind, ASLANViolationKind::TooManyItems);
+    assert_eq!(violations[0].path, vec!["steps".to_string()]);
+
+    // The same violation is not reported again when the result is validated on close
+    parser.parse_next("Eat[asland]Repeat");
+    parser.close();
+    assert_eq!(parser.get_violations().len(), 1);
+}
+
+#[test]
+fn reports_disallowed_instructions_voids_and_parts() {
+    let mut parser = recipe_parser();
+    parser.parse(
+        "[asland_title][aslanp]A[aslanp]B[asland_steps][aslana][asland][aslani_italic]Mix[aslana][asland_note][aslanv]",
+    );
+    let summary: Vec<(ASLANViolationKind, Vec<String>)> =
+        parser.get_violations().iter().map(|v| (v.kind, v.path.clone())).collect();
+    assert_eq!(
+        summary,
+        vec![
+            (ASLANViolationKind::TypeMismatch, vec!["title".to_string()]),
+            (ASLANViolationKind::DisallowedInstruction, vec!["steps".to_string(), "0".to_string()]),
+            (ASLANViolationKind::UnexpectedNull, vec!["note".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn reports_missing_required_fields_on_close() {
+    let mut parser = recipe_parser();
+    parser.parse("[asland_title]Pancakes");
+    let violations = parser.get_violations();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].kind, ASLANViolationKind::MissingField);
+    assert_eq!(violations[0].path, vec!["steps".to_string()]);
+}
+
+#[test]
+fn parse_next_checked_breaks_at_offending_delimiter() {
+    let mut parser = recipe_parser();
+    assert_eq!(parser.parse_next_checked("[asland_title]Pancakes"), ControlFlow::Continue(()));
+    let flow = parser.parse_next_checked("[asland_oops]this is never consumed");
+    match flow {
+        ControlFlow::Break(violation) => {
+            assert_eq!(violation.kind, ASLANViolationKind::UnknownField);
+            assert_eq!(violation.to_string(), "oops: field `oops` is not declared in the schema");
+        }
+        ControlFlow::Continue(()) => panic!("expected the parser to break on the unknown field"),
+    }
+    parser.close();
+    assert_eq!(parser.get_result().get("oops"), None);
+}
+
+#[test]
+fn reset_forgets_reported_violations() {
+    let mut parser = recipe_parser();
+    parser.parse_next("[asland_oops]x");
+    assert_eq!(parser.get_violations().len(), 1);
+    parser.reset();
+    assert!(parser.get_violations().is_empty());
+    parser.parse_next("[asland_oops]y");
+    assert_eq!(parser.get_violations().len(), 1);
+}
+
+#[test]
+fn no_violations_without_schema() {
+    let mut parser = ASLANParser::new();
+    parser.parse("[asland_anything][aslanv][asland_more][aslanp]x");
+    assert!(parser.get_violations().is_empty());
+}
@@ -0,0 +1,107 @@
+use aslan::{ASLANDiagnosticCode, ASLANError, ASLANParser, ASLANParserSettings, ASLANSchema, ASLANViolationKind};
+use serde_json::json;
+
+fn strict_parser() -> ASLANParser {
+    ASLANParser::with_settings(ASLANParserSettings {
+        strict: true,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn returns_result_of_well_formed_input() {
+    let result = strict_parser().try_parse("[asland_a]1[asland_b][aslano][asland_c]2[aslano]");
+    assert_eq!(result, Ok(json!({ "_default": null, "a": "1", "b": { "c": "2" } })));
+}
+
+#[test]
+fn fails_on_input_lenient_mode_repairs() {
+    let cases = [
+        ("[asland_l][aslana][asland_x]a[aslana]", ASLANDiagnosticCode::InvalidArrayIndex),
+        ("[asland_a][aslanv][aslanv]", ASLANDiagnosticCode::DuplicateVoid),
+        ("[asland_a][aslanv]text", ASLANDiagnosticCode::ContentAfterVoid),
+        ("[asland_a][aslane_T]text", ASLANDiagnosticCode::UnterminatedEscape),
+        ("[asland_a]1[aslano]", ASLANDiagnosticCode::ExtraneousClose),
+        ("[asland_a]1[aslanx]", ASLANDiagnosticCode::ReservedDelimiter),
+        ("[asland_a:f]1[asland_a:l]2", ASLANDiagnosticCode::ConflictingDuplicateBehavior),
+        ("[asland_a]1[aslan_d_b]2", ASLANDiagnosticCode::MalformedDelimiter),
+    ];
+    for (input, code) in cases {
+        let error = strict_parser().try_parse(input).unwrap_err();
+        assert_eq!(error.code(), Some(code), "{}", input);
+
+        // Lenient mode repairs the same input
+        let mut lenient = ASLANParser::new();
+        assert_eq!(lenient.try_parse(input), Ok(ASLANParser::new().parse(input)));
+    }
+}
+
+#[test]
+fn stops_consuming_input_at_the_first_error() {
+    let mut parser = strict_parser();
+    assert_eq!(parser.try_parse_next("[asland_a]1"), Ok(()));
+    let error = parser.try_parse_next("[aslanx]2[asland_b]3").unwrap_err();
+    assert_eq!(error.code(), Some(ASLANDiagnosticCode::ReservedDelimiter));
+    assert_eq!(error.to_string(), "malformed ASLAN at byte 11: `[aslanx]` has a reserved suffix and is dropped");
+    assert_eq!(parser.get_result()["a"], json!("1"));
+    assert!(parser.get_result().get("b").is_none());
+}
+
+#[test]
+fn fails_on_schema_violations() {
+    let mut parser = ASLANParser::with_settings(ASLANParserSettings {
+        strict: true,
+        schema: Some(ASLANSchema::object().with_field("a", ASLANSchema::string()).with_field("b", ASLANSchema::string())),
+        ..Default::default()
+    });
+    match parser.try_parse("[asland_a]1") {
+        Err(ASLANError::Violation(violation)) => {
+            assert_eq!(violation.kind, ASLANViolationKind::MissingField);
+            assert_eq!(violation.path, vec!["b".to_string()]);
+        }
+        other => panic!("expected a violation, got {:?}", other),
+    }
+}
+
+#[test]
+fn try_close_reports_repairs_made_while_streaming_leniently() {
+    let mut parser = strict_parser();
+    parser.parse_next("[asland_a][aslane_T]1");
+    let error = parser.try_close().unwrap_err();
+    assert!(matches!(&error, ASLANError::UnterminatedEscape(diagnostic) if diagnostic.offset == 21), "{:?}", error);
+    assert_eq!(error.code(), Some(ASLANDiagnosticCode::UnterminatedEscape));
+}
+
+#[test]
+fn accepts_blocks_closed_at_the_end_of_input() {
+    let result = strict_parser().try_parse("[asland_person][aslano][asland_name]Bob");
+    assert_eq!(result, Ok(json!({ "_default": null, "person": { "name": "Bob" } })));
+}
+
+#[test]
+fn try_close_returns_every_result_with_multi_aslan_output() {
+    let settings = ASLANParserSettings {
+        strict: true,
+        multi_aslan_output: true,
+        strict_start: true,
+        strict_end: true,
+        ..Default::default()
+    };
+    let input = "[aslang][asland_a]1[aslans][aslang][asland_a]2";
+    let expected = json!([{ "_default": null, "a": "1" }, { "_default": null, "a": "2" }]);
+    assert_eq!(ASLANParser::with_settings(settings.clone()).try_parse(input), Ok(expected.clone()));
+
+    let mut parser = ASLANParser::with_settings(settings);
+    parser.try_parse_next(input).unwrap();
+    assert_eq!(parser.try_close(), Ok(expected));
+}
+
+#[test]
+fn reset_forgets_earlier_repairs() {
+    let mut parser = strict_parser();
+    let error = parser.try_parse("[asland_a]1[aslanx]").unwrap_err();
+    assert_eq!(error.code(), Some(ASLANDiagnosticCode::ReservedDelimiter));
+    parser.reset();
+    assert!(parser.diagnostics().is_empty());
+    assert_eq!(parser.try_parse("[asland_a]x"), Ok(json!({ "_default": null, "a": "x" })));
+}
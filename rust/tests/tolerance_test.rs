@@ -0,0 +1,88 @@
+use aslan::{ASLANDiagnosticCode, ASLANParser, ASLANParserSettings, ASLANTolerance};
+use serde_json::json;
+
+fn tolerant_parser(tolerance: ASLANTolerance) -> ASLANParser {
+    ASLANParser::with_settings(ASLANParserSettings::builder().tolerance(tolerance).build().unwrap())
+}
+
+fn repairs(parser: &ASLANParser) -> Vec<String> {
+    parser
+        .diagnostics()
+        .into_iter()
+        .filter(|diagnostic| diagnostic.code == ASLANDiagnosticCode::RepairedDelimiter)
+        .map(|diagnostic| diagnostic.message)
+        .collect()
+}
+
+#[test]
+fn rejects_near_misses_by_default() {
+    let mut parser = ASLANParser::new();
+    let result = parser.parse("[ASLANd_a]1[aslan d_b]2[aslan_d_c]3[aslandd]4\\[asland_e\\]5");
+    assert_eq!(result["_default"], json!("[ASLANd_a]1[aslan d_b]2[aslan_d_c]3[aslandd]4\\[asland_e\\]5"));
+}
+
+#[test]
+fn accepts_each_near_miss_with_all_rules() {
+    let mut parser = tolerant_parser(ASLANTolerance::all());
+    let result = parser.parse("[ASLANd_a]1[aslan d_b]2[aslan_d_c]3[aslandd]4\\[asland_e\\]5[ASLAND_f]6");
+    assert_eq!(result, json!({ "_default": null, "a": "1", "b": "2", "c": "3", "d": "4", "e": "5", "f": "6" }));
+    assert_eq!(repairs(&parser), vec![
+        "`[ASLANd_a]` is read as a delimiter despite the case of the prefix",
+        "`[aslan d_b]` is read as a delimiter despite a space after the prefix",
+        "`[aslan_d_c]` is read as a delimiter despite an underscore after the prefix",
+        "`[aslandd]` is read as a delimiter despite a missing underscore before the name",
+        "`\\[asland_e\\]` is read as a delimiter despite escaped brackets",
+        "`[ASLAND_f]` is read as a delimiter despite the case of the prefix, the case of the suffix",
+    ]);
+}
+
+#[test]
+fn toggles_each_rule_separately() {
+    let mut parser = tolerant_parser(ASLANTolerance {
+        space_after_prefix: true,
+        ..Default::default()
+    });
+    let result = parser.parse("[aslan d_a]1[aslan_d_b]2");
+    assert_eq!(result, json!({ "_default": null, "a": "1[aslan_d_b]2" }));
+
+    let mut parser = tolerant_parser(ASLANTolerance {
+        missing_underscore: true,
+        ..Default::default()
+    });
+    let result = parser.parse("[asland_a][aslano][aslanditem]x[aslanibold]");
+    assert_eq!(result["a"], json!({ "item": "x" }));
+}
+
+#[test]
+fn keeps_broken_escaped_brackets_as_text() {
+    let mut parser = tolerant_parser(ASLANTolerance {
+        escaped_brackets: true,
+        ..Default::default()
+    });
+    let result = parser.parse("\\[asland_a\\]x \\n \\[asland_b\\x]");
+    assert_eq!(result, json!({ "_default": null, "a": "x \\n \\[asland_b\\x]" }));
+}
+
+#[test]
+fn repairs_delimiters_split_across_chunks() {
+    let input = "intro \\[ASLAN d_title\\]Pancakes[aslan_o]";
+    let mut whole = tolerant_parser(ASLANTolerance::all());
+    let expected = whole.parse(input);
+    assert_eq!(expected, json!({ "_default": "intro ", "title": "Pancakes" }));
+    for split in 0..input.len() {
+        let mut parser = tolerant_parser(ASLANTolerance::all());
+        parser.parse_next(&input[..split]);
+        parser.parse_next(&input[split..]);
+        parser.close();
+        assert_eq!(parser.get_result(), expected, "split at {}", split);
+        assert_eq!(repairs(&parser).len(), 2);
+    }
+}
+
+#[test]
+fn fails_repaired_input_in_strict_mode() {
+    let settings = ASLANParserSettings::builder().tolerance(ASLANTolerance::all()).strict(true).build().unwrap();
+    let mut parser = ASLANParser::with_settings(settings);
+    let error = parser.try_parse("[ASLANd_a]1").unwrap_err();
+    assert_eq!(error.code(), Some(ASLANDiagnosticCode::RepairedDelimiter));
+}
@@ -57,3 +57,17 @@ fn parses_simple_string_with_object_and_void_in_a_previous_duplicate_key_field()
         }
     }));
 }
+
+#[test]
+fn parses_void_directly_followed_by_delimiter() {
+    let mut parser = ASLANParser::new();
+    let result = parser.parse("[asland_a][aslanv][asland_b][aslano][asland_c][aslanv][aslano][asland_d]x");
+    assert_eq!(result, json!({
+        "_default": null,
+        "a": null,
+        "b": {
+            "c": null
+        },
+        "d": "x"
+    }));
+}
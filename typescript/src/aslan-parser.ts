@@ -285,10 +285,22 @@ export class ASLANParser {
   }
 
   private exitDelimiterIntoDATA(char: string) {
-    this.currentValue += this.delimiterBuffer + char;
+    this.currentValue += this.delimiterBuffer;
     this.delimiterBuffer = '';
     this.currentDelimiter = null;
     this.state = ASLANParserState.DATA;
+    if (char === '[' && !this.parsingLocked) {
+      //Spec: Every `[` starts buffering a possible delimiter (Implementation specifics), so the char that broke this one may start the next, e.g. `[x[asland_y]`
+      this.storeCurrentValue();
+      this.state = ASLANParserState.MAYBE_DELIMITER;
+      this.delimiterBuffer += char;
+      return;
+    }
+    this.currentValue += char;
+    if (!this.parsingLocked) {
+      //Store now, as the next char may already start another delimiter
+      this.storeCurrentValue();
+    }
   }
 
   private handleNextChar(char: string) {
@@ -1050,6 +1062,8 @@ export class ASLANParser {
       this.delimiterBuffer = '';
       this.currentValue = '';
       this.stack[this.stack.length - 1].voidFields[this.getCurrentKey()] = true;
+      //Spec: A void field is null. Store the null now, as the next char may already start another delimiter
+      this.storeCurrentValue();
       return;
     }
     //Spec: Void delimiters have no <CONTENT> or args
@@ -20,4 +20,24 @@ describe('ASLANParser Delimiter-like', () => {
       ],
     });
   });
+
+  test('parses delimiter directly after broken delimiter', () => {
+    const result = parser.parse('[asland_a][x[asland_b]see [asl[asland_c]arr[[asland_d]done');
+    expect(result).toEqual({
+      _default: null,
+      a: '[x',
+      b: 'see [asl',
+      c: 'arr[',
+      d: 'done',
+    });
+  });
+
+  test('keeps broken delimiter followed by delimiter', () => {
+    const result = parser.parse('[asland_a]1[asl][asland_b]2');
+    expect(result).toEqual({
+      _default: null,
+      a: '1[asl]',
+      b: '2',
+    });
+  });
 });
@@ -63,4 +63,18 @@ describe('ASLANParser Void', () => {
       },
     });
   });
+
+  test('parses void directly followed by delimiter', () => {
+    const result = parser.parse(
+      '[asland_a][aslanv][asland_b][aslano][asland_c][aslanv][aslano][asland_d]x',
+    );
+    expect(result).toEqual({
+      _default: null,
+      a: null,
+      b: {
+        c: null,
+      },
+      d: 'x',
+    });
+  });
 });